@@ -0,0 +1,2 @@
+export const ArrowIcon = 'arrow';
+export const CloseIcon = 'close';
@@ -0,0 +1 @@
+import { platform } from 'isomorphic';
@@ -0,0 +1,4 @@
+const a = require('./a.cjs');
+const path = require('path');
+
+module.exports = { a, b: require.resolve('./b.cjs'), sep: path.sep };
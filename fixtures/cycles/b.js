@@ -0,0 +1,4 @@
+import { a } from './a.js';
+import { index } from './index.js';
+
+export const b = () => [a, index];
@@ -0,0 +1,3 @@
+import { render } from './static.js';
+
+export const open = () => import('./lazy.js').then(render);
@@ -0,0 +1,3 @@
+import { render } from './static.js';
+
+export default render;
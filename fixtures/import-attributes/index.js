@@ -0,0 +1,5 @@
+import data from './data.json' with { type: 'json' };
+import styles from './styles.css' with { type: 'css' };
+import { helper } from './helper.js';
+
+export default [data, styles, helper];
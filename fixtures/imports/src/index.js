@@ -0,0 +1,2 @@
+import { format } from '#utils/date';
+import config from '#config';
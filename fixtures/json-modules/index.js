@@ -0,0 +1,3 @@
+import config from './config.json';
+
+export default config;
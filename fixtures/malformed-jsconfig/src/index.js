@@ -0,0 +1,3 @@
+import { format } from "./format.js";
+
+export default format;
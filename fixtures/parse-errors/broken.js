@@ -0,0 +1,3 @@
+import './never.js';
+
+export const = 1;
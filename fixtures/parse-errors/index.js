@@ -0,0 +1,2 @@
+import './broken.js';
+import './ok.js';
@@ -0,0 +1 @@
+export const never = true;
@@ -0,0 +1,17 @@
+#!/usr/bin/env node
+/* eslint-disable */
+
+const RAW_RUNTIME_STATE =
+'{\
+  "__info": [],\
+  "dependencyTreeRoots": [{"name": "pnp-fixture", "reference": "workspace:."}],\
+  "enableTopLevelFallback": true,\
+  "ignorePatternData": null,\
+  "fallbackExclusionList": [],\
+  "fallbackPool": [],\
+  "packageRegistryData": [\
+    [null, [[null, {"packageLocation": "./", "packageDependencies": [["dep", "npm:1.0.0"]], "linkType": "SOFT"}]]],\
+    ["dep", [["npm:1.0.0", {"packageLocation": "./.yarn/unplugged/dep-npm-1.0.0/node_modules/dep/", "packageDependencies": [["dep", "npm:1.0.0"]], "linkType": "HARD"}]]],\
+    ["pnp-fixture", [["workspace:.", {"packageLocation": "./", "packageDependencies": [["dep", "npm:1.0.0"]], "linkType": "SOFT"}]]]\
+  ]\
+}';
@@ -0,0 +1 @@
+﻿export { helper as run } from "./helper.js";
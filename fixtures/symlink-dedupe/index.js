@@ -0,0 +1,4 @@
+import { shared } from './shared/index.js';
+import { linked } from './linked/index.js';
+
+export default [shared, linked];
@@ -0,0 +1 @@
+import { shared } from './shared/index.js';
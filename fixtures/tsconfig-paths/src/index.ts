@@ -0,0 +1,4 @@
+import { Button } from "@components/button";
+import { helper } from "@app/helper";
+
+export { Button, helper };
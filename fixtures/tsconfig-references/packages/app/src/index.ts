@@ -0,0 +1,3 @@
+import { format } from '@acme/shared';
+
+export const message = format('hello');
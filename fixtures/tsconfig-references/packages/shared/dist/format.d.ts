@@ -0,0 +1 @@
+export declare const format: (value: string) => string;
@@ -0,0 +1 @@
+export type Heavy = string;
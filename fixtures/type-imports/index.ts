@@ -0,0 +1,6 @@
+import type { Heavy } from './heavy';
+import { type Props } from './props';
+export { type Light } from './light';
+import { light } from './light';
+
+export const value: Heavy | Props = light;
@@ -0,0 +1,2 @@
+export type Light = number;
+export const light = 1;
@@ -0,0 +1 @@
+export type Props = { label: string };
@@ -0,0 +1,4 @@
+import './cli';
+import logo from './logo.svg';
+
+export default logo;
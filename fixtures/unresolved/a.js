@@ -0,0 +1,3 @@
+import './missing.js';
+
+export const a = 1;
@@ -0,0 +1,4 @@
+import { a } from './a.js';
+import optional from 'optional-peer';
+
+export default [a, optional];
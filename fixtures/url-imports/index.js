@@ -0,0 +1,5 @@
+import React from "https://esm.sh/react";
+import data from "data:text/javascript,export default 1";
+import { local } from "./local.js";
+
+export { React, data, local };
@@ -0,0 +1,3 @@
+import App from './App.vue';
+
+App.mount();
@@ -3,25 +3,962 @@
 
 /* auto-generated by NAPI-RS */
 
-export declare function resolveRs(
+export declare function resolveRs(importer: string, importee: string, options: ResolverOptions): string
+/** Like `resolve_rs`, but resolves as if imported from a file inside `context_dir`. */
+export declare function resolveFromDirectoryRs(contextDir: string, importee: string, options: ResolverOptions): string
+export interface ResolveManyResult {
+  /** The resolved path, if `importee` could be resolved */
+  path?: string
+  /** The reason resolution failed, if it did */
+  error?: ResolutionError
+}
+/**
+ * Resolves every importee against the same importer with a single resolver,
+ * so the resolver's cache is shared across the whole batch.
+ */
+export declare function resolveManyRs(
+  importer: string,
+  importees: Array<string>,
+  options: ResolverOptions,
+): Array<ResolveManyResult>
+/**
+ * Like `resolve_rs`, but returns the package and query information of the resolution
+ * alongside the path.
+ */
+export declare function resolveWithDetailsRs(
   importer: string,
   importee: string,
-  conditionNames: Array<string>,
-  mainFields: Array<string>,
-  extensions: Array<string>,
-  tsconfigConfigFile?: string | undefined | null,
-  tsconfigReferences?: Array<string> | undefined | null,
-): string
-export declare function isBarrelFileRs(source: string, amountOfExportsToConsiderModuleAsBarrel: number): boolean
+  options: ResolverOptions,
+): ResolutionDetails
+/** Resolves on the libuv thread pool instead of blocking the main thread. */
+export declare function resolveAsyncRs(importer: string, importee: string, options: ResolverOptions): Promise<string>
+/** Finds the `package.json` owning `path`, returning its `name` and `version`. */
+export declare function getPackageForPathRs(path: string): PackageInfo | null
+/**
+ * `file_path` selects the TypeScript, JSX and CommonJS parsing of `source` from its
+ * extension, unknown or missing extensions are parsed as plain ES modules.
+ */
+export declare function isBarrelFileRs(
+  source: string,
+  amountOfExportsToConsiderModuleAsBarrel: number,
+  filePath?: string | undefined | null,
+): boolean
+/**
+ * Classifies `(path, source)` pairs in parallel, the path of each pair taking the place of
+ * `options.file_path`.
+ */
+export declare function isBarrelFilesRs(
+  files: Array<[string, string]>,
+  options: BarrelFileOptions,
+): Array<BarrelClassification>
+/** Like `classify_barrel_file_rs`, but reads the file at `path` instead of taking its source. */
+export declare function isBarrelFilePathRs(path: string, options: BarrelFileOptions): BarrelClassification
+/** Like `is_barrel_file_rs`, but returns the counts the classification is based on. */
+export declare function classifyBarrelFileRs(source: string, options: BarrelFileOptions): BarrelClassification
 export declare function countModuleGraphSizeRs(
   entryPoints: Array<string>,
   basePath: string,
-  conditionNames: Array<string>,
-  mainFields: Array<string>,
-  extensions: Array<string>,
   ignoreModuleExtensions: Array<string>,
   builtinModules: Array<string>,
-  tsconfigConfigFile: string | undefined | null,
-  tsconfigReferences: Array<string> | undefined | null,
-  alias: Array<[string, Array<string>]>,
+  options: ResolverOptions,
+  graphOptions?: ModuleGraphOptions | undefined | null,
 ): number
+/** Counts on the libuv thread pool instead of blocking the main thread. */
+export declare function countModuleGraphSizeAsyncRs(
+  entryPoints: Array<string>,
+  basePath: string,
+  ignoreModuleExtensions: Array<string>,
+  builtinModules: Array<string>,
+  options: ResolverOptions,
+  graphOptions?: ModuleGraphOptions | undefined | null,
+): Promise<number>
+/** Like `count_module_graph_size_rs`, but also returns the modules reached. */
+export declare function countModuleGraphModulesRs(
+  entryPoints: Array<string>,
+  basePath: string,
+  ignoreModuleExtensions: Array<string>,
+  builtinModules: Array<string>,
+  options: ResolverOptions,
+  graphOptions?: ModuleGraphOptions | undefined | null,
+): ModuleGraphSize
+/**
+ * Like `count_module_graph_modules_rs`, but for each entry point on its own as well as for
+ * all of them together.
+ */
+export declare function countModuleGraphPerEntryRs(
+  entryPoints: Array<string>,
+  basePath: string,
+  ignoreModuleExtensions: Array<string>,
+  builtinModules: Array<string>,
+  options: ResolverOptions,
+  graphOptions?: ModuleGraphOptions | undefined | null,
+): ModuleGraphBreakdown
+/**
+ * Walks the same graph as `count_module_graph_size_rs`, returning the modules reached and
+ * the imports between them.
+ */
+export declare function buildModuleGraphRs(
+  entryPoints: Array<string>,
+  basePath: string,
+  ignoreModuleExtensions: Array<string>,
+  builtinModules: Array<string>,
+  options: ResolverOptions,
+  graphOptions?: ModuleGraphOptions | undefined | null,
+): ModuleGraph
+/**
+ * Walks the same graph as `build_module_graph_rs`, returning it as Graphviz DOT text with
+ * the barrel files classified by `barrel_options` filled in.
+ */
+export declare function exportModuleGraphDotRs(
+  entryPoints: Array<string>,
+  basePath: string,
+  ignoreModuleExtensions: Array<string>,
+  builtinModules: Array<string>,
+  options: ResolverOptions,
+  graphOptions: ModuleGraphOptions | undefined | null,
+  barrelOptions: BarrelFileOptions,
+): string
+/**
+ * Walks the same graph as `build_module_graph_rs`, returning it as JSON with the size and
+ * import counts of each module.
+ */
+export declare function exportModuleGraphJsonRs(
+  entryPoints: Array<string>,
+  basePath: string,
+  ignoreModuleExtensions: Array<string>,
+  builtinModules: Array<string>,
+  options: ResolverOptions,
+  graphOptions?: ModuleGraphOptions | undefined | null,
+): string
+export interface ModuleGraphOptions {
+  /**
+   * Follow imports at most this many hops away from the entry points, which are 0 hops
+   * away, unlimited by default
+   */
+  maxDepth?: number
+  /**
+   * Stop once this many modules are reached, leaving out the modules past it, for callers
+   * that only need to know whether a graph exceeds a budget
+   */
+  stopAt?: number
+  /**
+   * Stop once this many modules are reached, like `stop_at`, as a safeguard against graphs
+   * too large to walk in an editor
+   */
+  maxModules?: number
+  /**
+   * Stop visiting modules once the traversal has taken this long, as a safeguard against
+   * graphs too slow to walk in an editor
+   */
+  timeoutMs?: number
+  /**
+   * Threads to parse and resolve modules on, defaults to the available parallelism. Results
+   * are the same whatever the concurrency, and walks with the same concurrency share their
+   * threads
+   */
+  concurrency?: number
+  /**
+   * Called every `progress_interval` visited modules. Calls are queued to the main thread, so
+   * the synchronous functions only make them once they return
+   */
+  onProgress?: (progress: ModuleGraphProgress) => void
+  /**
+   * A `ModuleGraphCancellation` to stop the traversal with, which then fails with a
+   * `Cancelled` error
+   */
+  cancellation?: ModuleGraphCancellation
+  /** How many modules to visit between `on_progress` calls, defaults to 100 */
+  progressInterval?: number
+  /**
+   * Glob patterns, relative to `base_path`, of modules to leave out of the graph along with
+   * the imports only they lead to, e.g. `**\/*.test.ts` or `**\/__mocks__/**`
+   */
+  exclude?: Array<string>
+  /**
+   * Glob patterns or directories, relative to `base_path`, of the modules to parse. Modules
+   * outside of them, such as generated code in `dist/`, are counted without following their
+   * imports. Every module is parsed by default
+   */
+  include?: Array<string>
+  /**
+   * Whether to follow `import()` expressions, which bundlers usually split into chunks that
+   * don't load with the importer, defaults to `true`
+   */
+  followDynamicImports?: boolean
+  /**
+   * Whether to follow `require('./a')` and `require.resolve('./a')` calls with a string
+   * literal, for the CommonJS modules of packages, defaults to `false`. Optional requires
+   * guarded by a `try` that fail to resolve are reported like any unresolved import
+   */
+  followRequireCalls?: boolean
+  /** Whether to look for import cycles among the static imports, for `cycles` */
+  detectCycles?: boolean
+  /**
+   * Whether to count each package under `node_modules` as a single module named after the
+   * package, e.g. `react` or `@scope/name`, without following its imports
+   */
+  collapsePackages?: boolean
+  /**
+   * Whether to also split the first-party modules reached, those outside of `node_modules`,
+   * by the workspace package they belong to, for `workspace_packages`, defaults to `false`
+   */
+  splitWorkspacePackages?: boolean
+  /** What to do with imports that fail to resolve, defaults to `error` */
+  onUnresolved?: UnresolvedImports
+  /**
+   * What to do with modules without a JavaScript, TypeScript or component extension, such as
+   * `LICENSE` or bin shims, defaults to `leaf`
+   */
+  unknownExtensions?: UnknownExtensions
+  /**
+   * Regex patterns of specifiers to skip without resolving them, such as bundler virtual
+   * modules or query suffixes, e.g. `^virtual:` or `\\?raw$`
+   */
+  ignoreSpecifiers?: Array<string>
+  /**
+   * Whether to skip `import type` and other imports TypeScript erases, so the graph only
+   * has the modules loaded at runtime, defaults to `true`
+   */
+  skipTypeOnlyImports?: boolean
+  /**
+   * How much a module imported with a `type` import attribute, such as a JSON or CSS module,
+   * adds to `size`, defaults to 1. Such modules are counted without being parsed
+   */
+  importAttributeWeight?: number
+  /**
+   * Whether to count the modules reached through several symlinked paths once, by their real
+   * path, as with `symlinks: false` in pnpm workspaces. Such modules keep the path they were
+   * first reached through, defaults to `false`
+   */
+  dedupeRealPaths?: boolean
+  /**
+   * List this many of the modules imported by the most other modules in `most_imported`,
+   * none by default
+   */
+  mostImported?: number
+  /** Classify each module parsed while walking with these options, for `barrels` */
+  barrelOptions?: BarrelFileOptions
+  /**
+   * Count modules larger than this many bytes without reading or following their imports,
+   * such as generated bundles, unlimited by default
+   */
+  maxFileSize?: number
+  /**
+   * The extensions of stylesheets, images, fonts and other assets, which are counted
+   * without being parsed, e.g. `css` or `.svg`, defaults to the common stylesheet, image,
+   * font and media extensions
+   */
+  assetExtensions?: Array<string>
+  /** How assets are counted, defaults to `count` */
+  assets?: AssetImports
+  /** How `.json` modules are counted, which are never parsed, defaults to `count` */
+  jsonModules?: AssetImports
+  /**
+   * What the modules matching each pattern add to `size`, the first matching pattern
+   * winning over the other weights. Modules matching none weigh as usual
+   */
+  moduleWeights?: Array<ModuleWeight>
+}
+/** What the modules matching a glob pattern add to `size`, e.g. 0 for `**\/*.d.ts`. */
+export interface ModuleWeight {
+  /** Glob pattern relative to `base_path`, e.g. `**\/*.d.ts` or `src/generated/**` */
+  pattern: string
+  /** What each module adds to `size`, or with `per_byte` each byte of its source */
+  weight: number
+  /**
+   * Whether `weight` is per byte of source, so `size` approximates bundle cost, defaults
+   * to `false`
+   */
+  perByte?: boolean
+}
+export const enum AssetImports {
+  /** Count them as modules without parsing them, each adding 1 to `size` */
+  Count = 'count',
+  /** Count them as modules without parsing them, each adding its size in bytes to `size` */
+  Bytes = 'bytes',
+  /** Leave them out of the graph */
+  Skip = 'skip',
+}
+export const enum UnknownExtensions {
+  /** Count them without following their imports */
+  Leaf = 'leaf',
+  /** Parse them as ES modules */
+  Parse = 'parse',
+}
+/** The budget that stopped a traversal early. */
+export const enum ModuleGraphBudget {
+  /** More modules than `stop_at` or `max_modules` were left to visit */
+  Modules = 'modules',
+  /** The traversal took longer than `timeout_ms` */
+  Time = 'time',
+}
+/** A module that was counted but couldn't be walked as usual. */
+export interface ModuleGraphWarning {
+  /** The module, relative to `base_path` */
+  module: string
+  message: string
+}
+export const enum UnresolvedImports {
+  /** Fail the whole walk */
+  Error = 'error',
+  /** Leave them out of the graph */
+  Ignore = 'ignore',
+  /** Leave them out of the graph and list them in `unresolved` */
+  Collect = 'collect',
+}
+/** A module that failed to parse, which was counted without following its imports. */
+export interface ModuleGraphDiagnostic {
+  /** The module, relative to `base_path` */
+  module: string
+  /** The syntax errors oxc reported */
+  errors: Array<ParseDiagnostic>
+}
+/** An import that failed to resolve. */
+export interface UnresolvedImport {
+  /** The importing module, relative to `base_path`, or nothing for an entry point */
+  importer?: string
+  /** The specifier as written in the import, e.g. `./button` */
+  specifier: string
+  /** Why the specifier failed to resolve */
+  message: string
+}
+export interface ModuleGraphProgress {
+  /** Unique modules visited so far */
+  count: number
+  /** The module just visited, relative to `base_path` */
+  file: string
+}
+export interface ModuleGraphSize {
+  /** Unique modules reached */
+  count: number
+  /** The modules reached, relative to `base_path`, in the order they were visited */
+  modules: Array<string>
+  /** The size of the modules reached, in bytes of source */
+  totalBytes: number
+  /** The modules reached, each weighing 1 unless weighted otherwise */
+  size: number
+  /**
+   * The most imports between an entry point and a module reached, along the shortest chain
+   * of imports to each module
+   */
+  depth: number
+  /**
+   * The distinct `node_modules` packages the modules reached belong to, by the name in
+   * their nearest `package.json`, in the order they were first reached
+   */
+  packages: Array<string>
+  /** The modules reached outside of `node_modules` */
+  firstParty: ModuleGraphPartition
+  /** The modules reached inside of `node_modules` */
+  thirdParty: ModuleGraphPartition
+  /**
+   * With `split_workspace_packages`, the first-party modules reached by the workspace
+   * package they belong to, in the order the packages were first reached
+   */
+  workspacePackages: Array<WorkspacePackageSize>
+  /**
+   * With `most_imported`, the modules imported by the most other modules, most imported
+   * first
+   */
+  mostImported: Array<MostImportedModule>
+  /**
+   * With `barrel_options`, the modules reached that are barrel files, in the order they were
+   * visited. Modules counted without being parsed are never barrels
+   */
+  barrels: Array<string>
+  /** With `on_unresolved: "collect"`, the imports that failed to resolve */
+  unresolved: Array<UnresolvedImport>
+  /** The modules that couldn't be walked as usual */
+  warnings: Array<ModuleGraphWarning>
+  /** The modules that failed to parse, which were counted without following their imports */
+  diagnostics: Array<ModuleGraphDiagnostic>
+  /**
+   * Whether the traversal stopped early, with modules left to visit after reaching
+   * `stop_at` or `max_modules` modules or taking longer than `timeout_ms`
+   */
+  exceeded: boolean
+  /**
+   * The budget that stopped the traversal early, the modules reached until then being
+   * counted as usual
+   */
+  exceededBudget?: ModuleGraphBudget
+}
+/** The modules reached from each entry point on its own and from all of them together. */
+export interface ModuleGraphBreakdown {
+  /**
+   * The modules reached from each entry point, in the order of the entry points, each
+   * listing its modules in the order of `union.modules`. Budgets such as `stop_at` apply to
+   * `union`, and the entries of a `union` stopped early are `exceeded` as well
+   */
+  entries: Array<ModuleGraphSize>
+  /** The modules reached from any of the entry points */
+  union: ModuleGraphSize
+  /**
+   * The modules reached from each entry point and none of the others, in the order of the
+   * entry points
+   */
+  unique: Array<Array<string>>
+  /**
+   * The modules reached from more than one of the entry points, in the order of
+   * `union.modules`
+   */
+  shared: Array<string>
+}
+/** The modules reached from a set of entry points and the imports between them. */
+export interface ModuleGraph {
+  /** The modules reached, relative to `base_path`, in the order they were visited */
+  nodes: Array<string>
+  /** The static imports between `nodes`, in the order they were found */
+  edges: Array<ModuleGraphEdge>
+  /**
+   * The `import()` expressions of `nodes`, in the order they were found. With
+   * `follow_dynamic_imports: false` their importees are left out of `nodes`
+   */
+  dynamicEdges: Array<ModuleGraphEdge>
+  /** The size of `nodes`, in bytes of source */
+  totalBytes: number
+  /** `nodes`, each weighing 1 unless weighted otherwise */
+  size: number
+  /**
+   * The most imports between an entry point and any of `nodes`, along the shortest chain of
+   * imports to each node
+   */
+  depth: number
+  /**
+   * The distinct `node_modules` packages `nodes` belong to, by the name in their nearest
+   * `package.json`, in the order they were first reached
+   */
+  packages: Array<string>
+  /** The `nodes` outside of `node_modules` */
+  firstParty: ModuleGraphPartition
+  /** The `nodes` inside of `node_modules` */
+  thirdParty: ModuleGraphPartition
+  /**
+   * With `split_workspace_packages`, the `nodes` outside of `node_modules` by the workspace
+   * package they belong to, in the order the packages were first reached
+   */
+  workspacePackages: Array<WorkspacePackageSize>
+  /** With `most_imported`, the `nodes` imported by the most other nodes, most imported first */
+  mostImported: Array<MostImportedModule>
+  /**
+   * With `barrel_options`, the `nodes` that are barrel files, in the order they were visited.
+   * Nodes counted without being parsed are never barrels
+   */
+  barrels: Array<string>
+  /** With `on_unresolved: "collect"`, the imports that failed to resolve */
+  unresolved: Array<UnresolvedImport>
+  /** The modules that couldn't be walked as usual */
+  warnings: Array<ModuleGraphWarning>
+  /** The modules that failed to parse, which were counted without following their imports */
+  diagnostics: Array<ModuleGraphDiagnostic>
+  /**
+   * With `detect_cycles`, the import cycles between `nodes`. Each cycle lists the modules in
+   * import order, the last importing the first
+   */
+  cycles: Array<Array<string>>
+  /**
+   * Whether the traversal stopped early, with modules left to visit after reaching
+   * `stop_at` or `max_modules` modules or taking longer than `timeout_ms`
+   */
+  exceeded: boolean
+  /**
+   * The budget that stopped the traversal early, the modules reached until then being
+   * counted as usual
+   */
+  exceededBudget?: ModuleGraphBudget
+}
+/** The modules reached on one side of a split, such as those inside of `node_modules`. */
+export interface ModuleGraphPartition {
+  count: number
+  /** The size of the modules, in bytes of source */
+  totalBytes: number
+  /** The modules, each weighing 1 unless weighted otherwise */
+  size: number
+}
+/** The first-party modules reached that belong to one workspace package. */
+export interface WorkspacePackageSize {
+  /**
+   * The name in the nearest `package.json` of the modules, those without a named
+   * `package.json` being left out of `workspace_packages`
+   */
+  name: string
+  modules: ModuleGraphPartition
+}
+/** A module and how many other modules import it, statically or with `import()`. */
+export interface MostImportedModule {
+  /** The module, relative to `base_path` */
+  module: string
+  importers: number
+}
+export interface ModuleGraphEdge {
+  /** The importing module, relative to `base_path` */
+  importer: string
+  /** The imported module, relative to `base_path` */
+  importee: string
+  /** The specifier as written in the import, e.g. `./button` */
+  specifier: string
+}
+export interface BarrelFileOptions {
+  /** Files with more exports than this, and fewer local declarations than exports, are barrels */
+  amountOfExportsToConsiderModuleAsBarrel: number
+  /**
+   * Files with more `export ... from` statements than this are barrels, whatever their
+   * other exports and declarations, unset by default
+   */
+  amountOfReexportsToConsiderModuleAsBarrel?: number
+  /**
+   * Leave out type-only exports such as `export type { Foo } from './foo'`, which are
+   * erased at build time, defaults to `false`
+   */
+  ignoreTypeExports?: boolean
+  /**
+   * Export names left out of every count, e.g. `default`, `__esModule` or the `metadata` and
+   * `config` exports frameworks require of route files
+   */
+  ignoreExportNames?: Array<string>
+  /**
+   * Classify any file with an `export * from` as a barrel, whatever the thresholds,
+   * defaults to `false`
+   */
+  treatStarExportsAsBarrel?: boolean
+  /**
+   * Classify any file with an `export * as ns from` as a barrel, whatever the thresholds,
+   * defaults to `false`
+   */
+  treatNamespaceReexportsAsBarrel?: boolean
+  /**
+   * Classify files by the share of their exports that are re-exports, e.g. `0.8`, in place
+   * of `amount_of_exports_to_consider_module_as_barrel`, each `export * from` counts as one
+   */
+  reexportRatio?: number
+  /**
+   * Classify files by a score weighing each kind of export, in place of the thresholds and
+   * `reexport_ratio`
+   */
+  weights?: BarrelWeights
+  /**
+   * A regex matched against `file_path`, e.g. `(^|/)index\.(ts|js)x?$`, whose matches are
+   * classified with `amount_of_exports_to_consider_matching_file_as_barrel`
+   */
+  barrelFilenamePattern?: string
+  /**
+   * The export threshold of files matching `barrel_filename_pattern`, which are barrels as
+   * soon as they re-export anything when unset
+   */
+  amountOfExportsToConsiderMatchingFileAsBarrel?: number
+  /**
+   * A comment that opts a file out when it comes before any code, e.g. `barrel-file-ok`
+   * for `/* barrel-file-ok *\/`, unset by default
+   */
+  optOutComment?: string
+  /**
+   * Accept `return` at the top level, which Node allows in CommonJS modules, defaults to
+   * `true` for `.cjs` and `.cts` files and `false` otherwise
+   */
+  allowReturnOutsideFunction?: boolean
+  /** How `.d.ts`, `.d.mts` and `.d.cts` files are classified, defaults to `classify` */
+  declarationFiles?: DeclarationFiles
+  /**
+   * Selects the TypeScript, JSX and CommonJS parsing of the source from its extension,
+   * `.vue` and `.svelte` files from their `<script>` blocks, unknown or missing extensions
+   * as plain ES modules
+   */
+  filePath?: string
+}
+/** What each kind of export adds to a file's `score`. */
+export interface BarrelWeights {
+  /** Each name re-exported by `export { a } from` or `require`, defaults to `1` */
+  namedReexport?: number
+  /** Each `export * from`, defaults to `1` */
+  starExport?: number
+  /** Each `export * as ns from`, defaults to `1` */
+  namespaceReexport?: number
+  /** Each `export { default } from`, defaults to `1` */
+  defaultReexport?: number
+  /** Each local declaration, defaults to `-1` */
+  localDeclaration?: number
+  /**
+   * Files scoring more than this are barrels, defaults to
+   * `amount_of_exports_to_consider_module_as_barrel`
+   */
+  threshold?: number
+}
+export const enum DeclarationFiles {
+  /** Like any other file */
+  Classify = 'classify',
+  /** Never as barrels, they are erased at runtime */
+  NeverBarrel = 'never-barrel',
+  /** As `is_type_barrel` in place of `is_barrel` */
+  TypeBarrel = 'type-barrel',
+}
+/** The counts a barrel file classification is based on. */
+export interface BarrelClassification {
+  /**
+   * Exported names, including names re-exported from other modules and CommonJS
+   * `exports` properties
+   */
+  exportCount: number
+  /**
+   * Top level variable, function, class, interface, type alias, enum and namespace
+   * declarations, exported or not, with one per name a destructuring pattern binds, leaving
+   * out `require` bindings
+   */
+  localDeclarationCount: number
+  /**
+   * `export ... from` statements, including `export * from`, and properties of
+   * `module.exports` assigned a `require` call
+   */
+  reexportCount: number
+  /**
+   * `export * from` statements and `require` calls spread into `module.exports`, which
+   * re-export every name of their module
+   */
+  starExportCount: number
+  /**
+   * `export * as ns from` statements, each counted as a single export and re-export though
+   * they expose every name of their module
+   */
+  namespaceReexportCount: number
+  /**
+   * Names of `export { a as b }` lists without a `from` clause that refer to bindings
+   * declared in the file, which aren't re-exports
+   */
+  localReexportCount: number
+  /**
+   * Names of `export { a }` lists without a `from` clause that refer to imports, which pass
+   * through like `export { a } from './a'` and count as re-exported names for
+   * `reexport_ratio`
+   */
+  importedReexportCount: number
+  /** The sum of the `weights` of the file's exports, with the default weights when unset */
+  score: number
+  isBarrel: boolean
+  /** With `declaration_files: "type-barrel"`, whether a declaration file would be a barrel */
+  isTypeBarrel: boolean
+  /**
+   * Whether the file only re-exports from a single module, e.g. `export * from './impl'`
+   * or `export { default } from './impl'`, a pass-through rather than an aggregating barrel
+   */
+  isProxy: boolean
+  /**
+   * Whether the file runs top level code for its effects, such as polyfill imports or
+   * registrations, so importing its modules directly would skip those effects
+   */
+  hasSideEffects: boolean
+  /** Whether the file starts with `opt_out_comment`, for rules to leave it alone */
+  optedOut: boolean
+  /** Why the file is or isn't a barrel, e.g. to show in a lint report */
+  reason: string
+  /**
+   * A summary of the exports to interpolate into a report message, e.g. `12 of 14 exports
+   * are re-exports from 9 modules`
+   */
+  explanation: string
+  /** Where each re-export counted in `reexport_count` is, in source order */
+  reexports: Array<ReexportSpan>
+  /**
+   * The distinct specifiers of the modules re-exported from, in source order, including
+   * the imports of `imported_reexport_count`, e.g. to suggest direct imports
+   */
+  reexportedModules: Array<string>
+  /**
+   * Whether the source parsed without syntax errors, the counts only cover what oxc could
+   * recover otherwise
+   */
+  parsedCompletely: boolean
+  /** The syntax errors oxc reported */
+  errors: Array<ParseDiagnostic>
+}
+export interface ParseDiagnostic {
+  message: string
+  /** Byte offset of the start of the offending code */
+  start: number
+  /** Byte offset of the end of the offending code */
+  end: number
+  loc: SourceLocation
+}
+export interface ModuleCacheStats {
+  /** Modules whose imports were answered from the cache */
+  hits: number
+  /** Modules that had to be read and parsed */
+  misses: number
+  /** Distinct modules currently cached */
+  entries: number
+}
+export interface ParseCacheStats {
+  /** Classifications answered from the cache */
+  hits: number
+  /** Sources that had to be parsed */
+  misses: number
+  /** Distinct sources currently cached */
+  entries: number
+}
+/** A re-export statement, or the `require` call of a CommonJS re-export. */
+export interface ReexportSpan {
+  /** The module re-exported from, e.g. `./button` */
+  source: string
+  /** Byte offset of the start of the re-export */
+  start: number
+  /** Byte offset of the end of the re-export */
+  end: number
+  loc: SourceLocation
+}
+/** A range in ESLint's `loc` format. */
+export interface SourceLocation {
+  start: Position
+  end: Position
+}
+export interface Position {
+  /** 1-based line */
+  line: number
+  /** 0-based column in UTF-16 code units, as ESLint counts them */
+  column: number
+}
+export interface TsconfigResolveOptions {
+  /**
+   * Path to the `tsconfig.json` or `jsconfig.json` to read `paths` and `baseUrl` from, or
+   * to the directory containing it
+   */
+  configFile: string
+  /** Project references to load, defaults to the `references` field of `config_file` */
+  references?: Array<string>
+}
+/**
+ * Options forwarded to oxc_resolver.
+ *
+ * Alternate manifest names are not supported, there is no `description_files` option.
+ * Manifests are always read from `package.json`, oxc_resolver hardcodes the file name
+ * and has no equivalent of enhanced-resolve's `descriptionFiles`.
+ */
+export interface ResolverOptions {
+  /** Condition names for the `exports` field, e.g. `["node", "import"]` */
+  conditionNames?: Array<string>
+  /** Fields in `package.json` to read the entry point from, e.g. `["module", "main"]` */
+  mainFields?: Array<string>
+  /** Extensions to try, in order, e.g. `[".js", ".ts"]` */
+  extensions?: Array<string>
+  tsconfig?: TsconfigResolveOptions
+  /** Aliases as `[key, targets]` pairs, an empty `targets` ignores the module */
+  alias?: Array<[string, Array<string>]>
+  /** Path to the `.pnp.cjs` manifest of a Yarn Plug'n'Play project */
+  pnpManifest?: string
+  /** Fields in `package.json` that map `#internal` specifiers, defaults to `[["imports"]]` */
+  importsFields?: Array<Array<string>>
+  /** Whether to resolve symlinks to their real path, defaults to `true` */
+  symlinks?: boolean
+  /** Extensions to try in place of an explicit one, e.g. `[[".js", [".ts", ".js"]]]` */
+  extensionAlias?: Array<[string, Array<string>]>
+  /** Directories to look up bare specifiers in, defaults to `["node_modules"]` */
+  modules?: Array<string>
+  /** Directories that root-relative specifiers such as `/src/button` are resolved against */
+  roots?: Array<string>
+  /** Fields in `package.json` that remap files, such as `[["browser"]]`, ignored by default */
+  aliasFields?: Array<Array<string>>
+  /** Disable extension and directory index guessing for ESM-style resolution, defaults to `false` */
+  fullySpecified?: boolean
+  /** Absolute directories resolved paths must stay inside of, e.g. the workspace root */
+  restrictions?: Array<string>
+  /** Try `utils/foo` as a relative path before looking it up in `modules`, defaults to `false` */
+  preferRelative?: boolean
+  /** Separator used in returned paths, defaults to `native` */
+  pathSeparator?: PathSeparator
+  /** Aliases tried only after normal resolution failed, e.g. `[["crypto", ["crypto-browserify"]]]` */
+  fallback?: Array<[string, Array<string>]>
+  /** File names tried for directory imports, without extension, defaults to `["index"]` */
+  mainFiles?: Array<string>
+  /**
+   * Reject relative and absolute specifiers that leave out the file extension or point
+   * at a directory, reported as `MissingExtension`, defaults to `false`
+   */
+  enforceExtension?: boolean
+  /**
+   * Use the nearest `tsconfig.json` or `jsconfig.json` above each importer, outside of
+   * `node_modules`, in place of `tsconfig`, defaults to `false`
+   */
+  discoverTsconfig?: boolean
+  /**
+   * Report resolved paths whose casing differs from the files on disk, which break on
+   * case-sensitive filesystems, as `CaseMismatch`, defaults to `false`
+   */
+  caseSensitive?: boolean
+  /**
+   * Resolve files inside the `outDir` of a referenced tsconfig project to the sources they
+   * are built from, the way TypeScript follows project references, defaults to `false`
+   */
+  preferReferenceSource?: boolean
+}
+export const enum PathSeparator {
+  /** `\` on Windows and `/` everywhere else */
+  Native = 'native',
+  /** `/` on every platform, matching ESLint's `context.filename` on Windows */
+  Slash = 'slash',
+}
+export interface CacheStats {
+  /** Resolutions answered from the cache */
+  hits: number
+  /** Resolutions that had to go through the resolver */
+  misses: number
+  /** Distinct importer directory and specifier pairs currently cached */
+  entries: number
+}
+export const enum ResolveErrorCode {
+  /** No file matched the specifier */
+  NotFound = 'NotFound',
+  /** The tsconfig could not be found or read */
+  TsconfigError = 'TsconfigError',
+  /** A `package.json` `exports`/`imports` map was invalid or did not expose the specifier */
+  InvalidPackageTarget = 'InvalidPackageTarget',
+  /** The specifier resolved to a path outside of the `restrictions` */
+  Restricted = 'Restricted',
+  /** With `enforce_extension`, the specifier lacks the extension of the path it resolves to */
+  MissingExtension = 'MissingExtension',
+  /** With `case_sensitive`, the resolved path differs in casing from the one on disk */
+  CaseMismatch = 'CaseMismatch',
+  IoError = 'IoError',
+  Unknown = 'Unknown',
+}
+/** A failed resolution, thrown to JS as an `Error` carrying these fields as properties. */
+export interface ResolutionError {
+  code: ResolveErrorCode
+  specifier: string
+  /** The importing file, or the context directory when resolving from one */
+  importer: string
+  message: string
+}
+export interface ResolutionDetails {
+  /** The resolved path, without query and fragment */
+  path: string
+  /** `name` of the nearest `package.json` to the resolved path */
+  packageName?: string
+  /** `version` of the nearest `package.json` to the resolved path */
+  packageVersion?: string
+  packageJsonPath?: string
+  /** Whether the resolved path lives inside a `node_modules` directory */
+  isNodeModule: boolean
+  /** The `?query` of the importee, including the leading `?` */
+  query?: string
+  /** The `#fragment` of the importee, including the leading `#` */
+  fragment?: string
+  /** The tsconfig `paths` mapping the importee was resolved through */
+  tsconfigPaths?: TsconfigPathsMatch
+  /** Whether the importee is a Node builtin, `path` is then its `node:` prefixed name */
+  isBuiltin: boolean
+  /** Whether the importee is a URL such as `https://esm.sh/react`, `path` is then the URL */
+  isUrl: boolean
+}
+export interface PackageInfo {
+  name?: string
+  version?: string
+  packageJsonPath: string
+}
+export interface TsconfigPathsMatch {
+  /** The `compilerOptions.paths` key that matched, e.g. `@app/*` */
+  pattern: string
+  /** The mapped path that resolved, e.g. `src/*` */
+  target: string
+}
+/** A watched graph after a change to its modules changed its `count` or `size`. */
+export interface ModuleGraphChange {
+  /** The entry points of the graph, as passed to `watch` */
+  entryPoints: Array<string>
+  /**
+   * The modules that changed on disk, relative to `base_path`, in the order of the graph
+   * before the change. Empty when only files next to them were created or deleted
+   */
+  changed: Array<string>
+  /** `graph.count` before the change */
+  previousCount: number
+  /** `graph.size` before the change */
+  previousSize: number
+  graph: ModuleGraphSize
+}
+/**
+ * Classifies files with the options it was constructed with, parsing each distinct source
+ * only once so a barrel imported from many files isn't parsed again for every importer.
+ */
+export declare class BarrelAnalyzer {
+  constructor(options: BarrelFileOptions)
+  /** `file_path` takes the place of `options.file_path`. */
+  classify(source: string, filePath?: string | undefined | null): BarrelClassification
+  /** Like `classify`, but reads the file at `path` instead of taking its source. */
+  classifyPath(path: string): BarrelClassification
+  cacheStats(): ParseCacheStats
+  clearCache(): void
+}
+/**
+ * Walks module graphs with the options it was constructed with, keeping the imports of each
+ * module it parsed so the modules shared by many graphs are only parsed again once they
+ * change on disk.
+ */
+export declare class ModuleGraphAnalyzer {
+  constructor(
+    ignoreModuleExtensions: Array<string>,
+    builtinModules: Array<string>,
+    options: ResolverOptions,
+    graphOptions?: ModuleGraphOptions | undefined | null,
+  )
+  /** Like `count_module_graph_size_rs`. */
+  count(entryPoints: Array<string>, basePath: string): number
+  /** Like `count_module_graph_modules_rs`. */
+  countModules(entryPoints: Array<string>, basePath: string): ModuleGraphSize
+  /** Like `build_module_graph_rs`. */
+  build(entryPoints: Array<string>, basePath: string): ModuleGraph
+  /**
+   * Like `count_modules`, but answered from the graph counted for the same entry points
+   * before until one of its modules is passed to `invalidate`. Only the invalidated modules
+   * and those changed on disk are parsed again when the graph is walked anew
+   */
+  recount(entryPoints: Array<string>, basePath: string): ModuleGraphSize
+  /**
+   * Forgets the module at `path`, absolute or relative to `base_path`, after it changed, so
+   * `recount` walks the graphs reaching it again. Creating or deleting a module can change
+   * what the imports of other modules resolve to, which then have to be invalidated as well.
+   * Returns whether a graph counted by `recount` reached the module
+   */
+  invalidate(path: string): boolean
+  /**
+   * Watches the modules of the graph of `entry_points` and the directories they are in,
+   * walking it again with `recount` once no changes came for `debounce_ms`, 50 by default,
+   * and calling `on_change` when that changes its `count` or `size`. The graph is counted
+   * before returning, so errors walking it are thrown here. The watcher doesn't keep the
+   * process alive
+   */
+  watch(entryPoints: Array<string>, basePath: string, onChange: (err: Error | null, change: ModuleGraphChange) => void, debounceMs?: number | undefined | null): ModuleGraphWatcher
+  cacheStats(): ModuleCacheStats
+  clearCache(): void
+}
+/**
+ * Stops the module graph traversals it is passed to as `cancellation`, e.g. once ESLint moves
+ * on to the next file. Traversals check it before each module they visit or parse.
+ */
+export declare class ModuleGraphCancellation {
+  constructor()
+  cancel(): void
+  get cancelled(): boolean
+}
+/**
+ * A resolver that is constructed once and keeps oxc_resolver's filesystem cache
+ * alive between calls.
+ */
+export declare class ResolverHandle {
+  constructor(options: ResolverOptions)
+  resolve(importer: string, importee: string): string
+  resolveMany(importer: string, importees: Array<string>): Array<ResolveManyResult>
+  resolveWithDetails(importer: string, importee: string): ResolutionDetails
+  /** Resolves on the libuv thread pool, sharing this handle's cache. */
+  resolveAsync(importer: string, importee: string): Promise<string>
+  /** Resolves as if imported from a file inside `context_dir`. */
+  resolveFromDirectory(contextDir: string, importee: string): string
+  packageForPath(path: string): PackageInfo | null
+  cacheStats(): CacheStats
+  /**
+   * Drops every cached resolution and oxc_resolver's filesystem cache, e.g. after
+   * `node_modules` changed on disk.
+   */
+  clearCache(): void
+}
+/** Watches the modules of a graph until it is closed or garbage collected. */
+export declare class ModuleGraphWatcher {
+  /** Stops watching, after the check in progress if there is one. */
+  close(): void
+}
@@ -1,10 +1,218 @@
 import {
+  BarrelAnalyzer,
+  buildModuleGraphRs as build_module_graph_rs,
+  classifyBarrelFileRs as classify_barrel_file,
+  countModuleGraphModulesRs as count_module_graph_modules_rs,
+  countModuleGraphPerEntryRs as count_module_graph_per_entry_rs,
+  countModuleGraphSizeAsyncRs as count_module_graph_size_async_rs,
   countModuleGraphSizeRs as count_module_graph_size_rs,
+  exportModuleGraphDotRs as export_module_graph_dot_rs,
+  exportModuleGraphJsonRs as export_module_graph_json_rs,
+  getPackageForPathRs as get_package_for_path_rs,
+  isBarrelFilePathRs as is_barrel_file_path,
   isBarrelFileRs as is_barrel_file,
-  resolveRs as resolve_rs
+  isBarrelFilesRs as is_barrel_files,
+  ModuleGraphAnalyzer,
+  ModuleGraphCancellation,
+  resolveAsyncRs as resolve_async_rs,
+  resolveFromDirectoryRs as resolve_from_directory_rs,
+  resolveManyRs as resolve_many_rs,
+  resolveRs as resolve_rs,
+  resolveWithDetailsRs as resolve_with_details_rs,
+  ResolverHandle
 } from './rs.cjs';
 import { builtinModules } from "module";
 
+/**
+ * Maps the options accepted by this package onto the native `ResolverOptions` object.
+ *
+ * @param {{
+ *  mainFields?: string[],
+ *  exportConditions?: string[],
+ *  extensions?: string[],
+ *  alias?: Record<string, string[]> | [string, string[]][],
+ *  pnpManifest?: string,
+ *  importsFields?: string[][],
+ *  symlinks?: boolean,
+ *  extensionAlias?: Record<string, string[]> | [string, string[]][],
+ *  modules?: string[],
+ *  roots?: string[],
+ *  aliasFields?: string[][],
+ *  fullySpecified?: boolean,
+ *  restrictions?: string[],
+ *  preferRelative?: boolean,
+ *  pathSeparator?: 'native' | 'slash',
+ *  fallback?: Record<string, string[]> | [string, string[]][],
+ *  mainFiles?: string[],
+ *  enforceExtension?: boolean,
+ *  discoverTsconfig?: boolean,
+ *  caseSensitive?: boolean,
+ *  preferReferenceSource?: boolean,
+ * }} [options]
+ */
+function resolver_options(options = {}) {
+  const {
+    exportConditions = ["node", "import"],
+    mainFields = ["module", "browser", "main"],
+    extensions = [".js", ".ts", ".tsx", ".jsx", ".json", ".node"],
+    tsconfig,
+    pnpManifest,
+    importsFields,
+    symlinks,
+    modules,
+    roots,
+    aliasFields,
+    fullySpecified,
+    restrictions,
+    preferRelative,
+    pathSeparator,
+    mainFiles,
+    enforceExtension,
+    discoverTsconfig,
+    caseSensitive,
+    preferReferenceSource,
+  } = options;
+
+  // alias is expected to be a vector
+  let alias = options.alias ?? [];
+  if (options.alias && !Array.isArray(options.alias)) {
+    alias = Object.entries(options.alias);
+  }
+
+  let extensionAlias = options.extensionAlias;
+  if (extensionAlias && !Array.isArray(extensionAlias)) {
+    extensionAlias = Object.entries(extensionAlias);
+  }
+
+  let fallback = options.fallback;
+  if (fallback && !Array.isArray(fallback)) {
+    fallback = Object.entries(fallback);
+  }
+
+  return {
+    conditionNames: exportConditions,
+    mainFields,
+    extensions,
+    tsconfig,
+    alias,
+    pnpManifest,
+    importsFields,
+    symlinks,
+    extensionAlias,
+    modules,
+    roots,
+    aliasFields,
+    fullySpecified,
+    restrictions,
+    preferRelative,
+    pathSeparator,
+    fallback,
+    mainFiles,
+    enforceExtension,
+    discoverTsconfig,
+    caseSensitive,
+    preferReferenceSource,
+  };
+}
+
+/**
+ * Maps the options accepted by this package onto the native `ModuleGraphOptions` object.
+ *
+ * @param {{
+ *  maxDepth?: number,
+ *  stopAt?: number,
+ *  maxModules?: number,
+ *  timeoutMs?: number,
+ *  concurrency?: number,
+ *  onProgress?: (progress: { count: number, file: string }) => void,
+ *  cancellation?: ModuleGraphCancellation,
+ *  progressInterval?: number,
+ *  exclude?: string[],
+ *  include?: string[],
+ *  followDynamicImports?: boolean,
+ *  followRequireCalls?: boolean,
+ *  detectCycles?: boolean,
+ *  collapsePackages?: boolean,
+ *  splitWorkspacePackages?: boolean,
+ *  onUnresolved?: 'error' | 'ignore' | 'collect',
+ *  unknownExtensions?: 'leaf' | 'parse',
+ *  ignoreSpecifiers?: string[],
+ *  skipTypeOnlyImports?: boolean,
+ *  importAttributeWeight?: number,
+ *  dedupeRealPaths?: boolean,
+ *  mostImported?: number,
+ *  barrelOptions?: { amountOfExportsToConsiderModuleAsBarrel: number },
+ *  maxFileSize?: number,
+ *  assetExtensions?: string[],
+ *  assets?: "count" | "bytes" | "skip",
+ *  jsonModules?: "count" | "bytes" | "skip",
+ *  moduleWeights?: { pattern: string, weight: number, perByte?: boolean }[],
+ * }} [options]
+ */
+function graph_options(options = {}) {
+  const {
+    maxDepth,
+    stopAt,
+    maxModules,
+    timeoutMs,
+    concurrency,
+    onProgress,
+    cancellation,
+    progressInterval,
+    exclude,
+    include,
+    followDynamicImports,
+    followRequireCalls,
+    detectCycles,
+    collapsePackages,
+    splitWorkspacePackages,
+    onUnresolved,
+    unknownExtensions,
+    ignoreSpecifiers,
+    skipTypeOnlyImports,
+    importAttributeWeight,
+    dedupeRealPaths,
+    mostImported,
+    barrelOptions,
+    maxFileSize,
+    assetExtensions,
+    assets,
+    jsonModules,
+    moduleWeights,
+  } = options;
+
+  return {
+    maxDepth,
+    stopAt,
+    maxModules,
+    timeoutMs,
+    concurrency,
+    onProgress,
+    cancellation,
+    progressInterval,
+    exclude,
+    include,
+    followDynamicImports,
+    followRequireCalls,
+    detectCycles,
+    collapsePackages,
+    splitWorkspacePackages,
+    onUnresolved,
+    unknownExtensions,
+    ignoreSpecifiers,
+    skipTypeOnlyImports,
+    importAttributeWeight,
+    dedupeRealPaths,
+    mostImported,
+    barrelOptions,
+    maxFileSize,
+    assetExtensions,
+    assets,
+    jsonModules,
+    moduleWeights,
+  };
+}
+
 /**
  * @param {string} importer - the file that is importing the module
  * @param {string} importee - the module being imported
@@ -13,22 +221,164 @@ import { builtinModules } from "module";
  *  exportConditions?: string[],
 *  extensions?: string[],
 * }} [options]
-* @returns {string} the resolved path to the module
+* @returns {string} the resolved path to the module, `node:` prefixed for Node builtins and
+*  unchanged for URL specifiers such as `https://esm.sh/react`
+* @throws {Error & { code: string, specifier: string, importer: string }} when the module cannot be resolved,
+*  `code` is one of `NotFound`, `TsconfigError`, `InvalidPackageTarget`, `Restricted`, `MissingExtension`, `CaseMismatch`, `IoError` or `Unknown`
 */
 export function resolve(importer, importee, options) {
-  const mainFields = options?.mainFields || ["module", "browser", "main"];
-  const exportConditions = options?.exportConditions || ["node", "import"];
-  const extensions = options?.extensions || [".js", ".ts", ".tsx", ".jsx", ".json", ".node"];
-  const tsConfigFile = options?.tsconfig?.configFile;
-  const tsReferences = options?.tsconfig?.references;
+  return resolve_rs(importer, importee, resolver_options(options));
+}
+
+/**
+ * Like `resolve`, but resolves as if imported from a file inside `contextDir`.
+ *
+ * @param {string} contextDir - the directory to resolve the module from
+ * @param {string} importee - the module being imported
+ * @param {{
+ *  mainFields?: string[],
+ *  exportConditions?: string[],
+ *  extensions?: string[],
+ * }} [options]
+ * @returns {string} the resolved path to the module, `node:` prefixed for Node builtins and
+ *  unchanged for URL specifiers such as `https://esm.sh/react`
+ */
+export function resolve_from_directory(contextDir, importee, options) {
+  return resolve_from_directory_rs(contextDir, importee, resolver_options(options));
+}
+
+/**
+ * @param {string} importer - the file that is importing the modules
+ * @param {string[]} importees - the modules being imported
+ * @param {{
+ *  mainFields?: string[],
+ *  exportConditions?: string[],
+ *  extensions?: string[],
+ * }} [options]
+ * @returns {{
+ *  path?: string,
+ *  error?: { code: string, specifier: string, importer: string, message: string },
+ * }[]} one result per importee, in order
+ */
+export function resolve_many(importer, importees, options) {
+  return resolve_many_rs(importer, importees, resolver_options(options));
+}
 
-  return resolve_rs(importer, importee, exportConditions, mainFields, extensions, tsConfigFile, tsReferences);
+/**
+ * Like `resolve`, but runs on the libuv thread pool instead of blocking the event loop.
+ *
+ * @param {string} importer - the file that is importing the module
+ * @param {string} importee - the module being imported
+ * @param {{
+ *  mainFields?: string[],
+ *  exportConditions?: string[],
+ *  extensions?: string[],
+ * }} [options]
+ * @returns {Promise<string>} the resolved path to the module
+ */
+export function resolve_async(importer, importee, options) {
+  return resolve_async_rs(importer, importee, resolver_options(options));
+}
+
+/**
+ * Like `resolve`, but also reports the owning package and any query or fragment of the importee.
+ *
+ * @param {string} importer - the file that is importing the module
+ * @param {string} importee - the module being imported
+ * @param {{
+ *  mainFields?: string[],
+ *  exportConditions?: string[],
+ *  extensions?: string[],
+ * }} [options]
+ * @returns {{
+ *  path: string,
+ *  packageName?: string,
+ *  packageVersion?: string,
+ *  packageJsonPath?: string,
+ *  isNodeModule: boolean,
+ *  query?: string,
+ *  fragment?: string,
+ *  tsconfigPaths?: { pattern: string, target: string },
+ *  isBuiltin: boolean,
+ *  isUrl: boolean,
+ * }}
+ */
+export function resolve_with_details(importer, importee, options) {
+  return resolve_with_details_rs(importer, importee, resolver_options(options));
+}
+
+/**
+ * Finds the `package.json` owning `path`, the package root for files inside `node_modules`.
+ *
+ * @param {string} path - the file to look up
+ * @returns {{ name?: string, version?: string, packageJsonPath: string } | null}
+ */
+export function get_package_for_path(path) {
+  return get_package_for_path_rs(path);
+}
+
+/**
+ * Creates a resolver that can be reused across many `resolve` calls, keeping its cache warm.
+ *
+ * @param {{
+ *  mainFields?: string[],
+ *  exportConditions?: string[],
+ *  extensions?: string[],
+ *  alias?: Record<string, string[]> | [string, string[]][],
+ * }} [options]
+ * @returns {ResolverHandle}
+ */
+export function create_resolver(options = {}) {
+  return new ResolverHandle(resolver_options(options));
+}
+
+/**
+ * Creates a barrel file analyzer that parses each distinct source only once, for lint runs
+ * that classify the same barrel from many importers.
+ *
+ * @param {{
+ *  amountOfExportsToConsiderModuleAsBarrel: number,
+ *  ignoreTypeExports?: boolean,
+ *  ignoreExportNames?: string[],
+ *  treatStarExportsAsBarrel?: boolean,
+ *  treatNamespaceReexportsAsBarrel?: boolean,
+ *  reexportRatio?: number,
+ * }} options
+ * @returns {BarrelAnalyzer}
+ */
+export function create_barrel_analyzer(options) {
+  return new BarrelAnalyzer(options);
+}
+
+/**
+ * Creates a module graph analyzer that parses each module only once until it changes on disk,
+ * for lint runs that count many graphs sharing the same modules.
+ *
+ * @param {{
+ *  ignoreModuleExtensions?: string[],
+ *  maxDepth?: number,
+ *  exportConditions?: string[],
+ *  mainFields?: string[],
+ *  extensions?: string[],
+ * }} [options]
+ * @returns {ModuleGraphAnalyzer}
+ */
+export function create_module_graph_analyzer(options = {}) {
+  const { ignoreModuleExtensions = [] } = options;
+
+  return new ModuleGraphAnalyzer(
+    ignoreModuleExtensions,
+    builtinModules,
+    resolver_options(options),
+    graph_options(options)
+  );
 }
 
 /**
 * @param {string[]} entrypoints 
 * @param {{
 *  basePath?: string,
+*  maxDepth?: number,
 *  exportConditions?: string[],
 *  mainFields?: string[],
 *  extensions?: string[],
@@ -38,36 +388,277 @@ export function resolve(importer, importee, options) {
 export function count_module_graph_size(entrypoints, options = {}) {
   const {
     basePath = process.cwd(),
-    exportConditions = ["node", "import"],
-    mainFields = ["module", "browser", "main"],
-    extensions = [".js", ".ts", ".tsx", ".jsx", ".json", ".node"],
-    ignoreModuleExtensions,
-    tsconfig,
+    ignoreModuleExtensions = [],
   } = options;
 
-  const tsConfigFile = tsconfig?.configFile;
-  const tsReferences = tsconfig?.references;
-
-  // alias is expected to be a vector
-  let alias = options.alias ?? [];
-  if (options.alias && typeof options.alias === 'object') {
-    alias = Object.entries(options.alias);
-  }
-
   const processedEntrypoints = (typeof entrypoints === "string" ? [entrypoints] : entrypoints);
   const result = count_module_graph_size_rs(
     processedEntrypoints,
     basePath,
-    exportConditions,
-    mainFields,
-    extensions,
     ignoreModuleExtensions,
     builtinModules,
-    tsConfigFile,
-    tsReferences,
-    alias
+    resolver_options(options),
+    graph_options(options)
   );
   return result;
 }
 
-export { is_barrel_file };
+/**
+ * Like `count_module_graph_size`, but counts on the libuv thread pool instead of blocking the
+ * main thread. Aborting `signal` stops the count and rejects with its reason.
+ *
+ * @param {string | string[]} entrypoints
+ * @param {{
+ *  basePath?: string,
+ *  signal?: AbortSignal,
+ *  ignoreModuleExtensions?: string[],
+ *  maxDepth?: number,
+ *  exportConditions?: string[],
+ *  mainFields?: string[],
+ *  extensions?: string[],
+ * }} [options]
+ * @returns {Promise<number>}
+ */
+export function count_module_graph_size_async(entrypoints, options = {}) {
+  const {
+    basePath = process.cwd(),
+    ignoreModuleExtensions = [],
+    signal,
+  } = options;
+
+  if (signal?.aborted) {
+    return Promise.reject(signal.reason);
+  }
+  const cancellation = options.cancellation ?? new ModuleGraphCancellation();
+  const count = count_module_graph_size_async_rs(
+    typeof entrypoints === "string" ? [entrypoints] : entrypoints,
+    basePath,
+    ignoreModuleExtensions,
+    builtinModules,
+    resolver_options(options),
+    { ...graph_options(options), cancellation }
+  );
+  // Listen once the count started, so invalid options thrown above leave no listener behind
+  const cancel = () => cancellation.cancel();
+  signal?.addEventListener("abort", cancel, { once: true });
+
+  return count
+    .catch((error) => {
+      throw signal?.aborted ? signal.reason : error;
+    })
+    .finally(() => signal?.removeEventListener("abort", cancel));
+}
+
+/**
+ * Like `count_module_graph_size`, but also returns the modules reached, relative to `basePath`.
+ *
+ * @param {string | string[]} entrypoints
+ * @param {{
+ *  basePath?: string,
+ *  ignoreModuleExtensions?: string[],
+ *  maxDepth?: number,
+ *  exportConditions?: string[],
+ *  mainFields?: string[],
+ *  extensions?: string[],
+ * }} [options]
+ * @returns {{
+ *  count: number,
+ *  modules: string[],
+ *  totalBytes: number,
+ *  size: number,
+ *  depth: number,
+ *  packages: string[],
+ *  firstParty: { count: number, totalBytes: number, size: number },
+ *  thirdParty: { count: number, totalBytes: number, size: number },
+ *  workspacePackages: { name: string, modules: { count: number, totalBytes: number, size: number } }[],
+ *  mostImported: { module: string, importers: number }[],
+ *  barrels: string[],
+ *  unresolved: { importer?: string, specifier: string, message: string }[],
+ *  warnings: { module: string, message: string }[],
+ *  diagnostics: { module: string, errors: { message: string, start: number, end: number }[] }[],
+ *  exceeded: boolean,
+ *  exceededBudget?: "modules" | "time",
+ * }}
+ */
+export function count_module_graph_modules(entrypoints, options = {}) {
+  const {
+    basePath = process.cwd(),
+    ignoreModuleExtensions = [],
+  } = options;
+
+  return count_module_graph_modules_rs(
+    typeof entrypoints === "string" ? [entrypoints] : entrypoints,
+    basePath,
+    ignoreModuleExtensions,
+    builtinModules,
+    resolver_options(options),
+    graph_options(options)
+  );
+}
+
+/**
+ * Like `count_module_graph_modules`, but for each entry point on its own as well as for all of
+ * them together.
+ *
+ * @param {string[]} entrypoints
+ * @param {{
+ *  basePath?: string,
+ *  ignoreModuleExtensions?: string[],
+ *  maxDepth?: number,
+ *  exportConditions?: string[],
+ *  mainFields?: string[],
+ *  extensions?: string[],
+ * }} [options]
+ * @returns {{
+ *  entries: { count: number, modules: string[], totalBytes: number, exceeded: boolean }[],
+ *  union: { count: number, modules: string[], totalBytes: number, exceeded: boolean },
+ *  unique: string[][],
+ *  shared: string[],
+ * }}
+ */
+export function count_module_graph_per_entry(entrypoints, options = {}) {
+  const {
+    basePath = process.cwd(),
+    ignoreModuleExtensions = [],
+  } = options;
+
+  return count_module_graph_per_entry_rs(
+    entrypoints,
+    basePath,
+    ignoreModuleExtensions,
+    builtinModules,
+    resolver_options(options),
+    graph_options(options)
+  );
+}
+
+/**
+ * Walks the same graph as `count_module_graph_size`, returning the modules reached and the
+ * imports between them, relative to `basePath`.
+ *
+ * @param {string | string[]} entrypoints
+ * @param {{
+ *  basePath?: string,
+ *  ignoreModuleExtensions?: string[],
+ *  maxDepth?: number,
+ *  exportConditions?: string[],
+ *  mainFields?: string[],
+ *  extensions?: string[],
+ * }} [options]
+ * @returns {{
+ *  nodes: string[],
+ *  edges: { importer: string, importee: string, specifier: string }[],
+ *  dynamicEdges: { importer: string, importee: string, specifier: string }[],
+ *  totalBytes: number,
+ *  size: number,
+ *  depth: number,
+ *  packages: string[],
+ *  firstParty: { count: number, totalBytes: number, size: number },
+ *  thirdParty: { count: number, totalBytes: number, size: number },
+ *  workspacePackages: { name: string, modules: { count: number, totalBytes: number, size: number } }[],
+ *  mostImported: { module: string, importers: number }[],
+ *  barrels: string[],
+ *  cycles: string[][],
+ *  unresolved: { importer?: string, specifier: string, message: string }[],
+ *  warnings: { module: string, message: string }[],
+ *  diagnostics: { module: string, errors: { message: string, start: number, end: number }[] }[],
+ *  exceeded: boolean,
+ *  exceededBudget?: "modules" | "time",
+ * }}
+ */
+export function build_module_graph(entrypoints, options = {}) {
+  const {
+    basePath = process.cwd(),
+    ignoreModuleExtensions = [],
+  } = options;
+
+  return build_module_graph_rs(
+    typeof entrypoints === "string" ? [entrypoints] : entrypoints,
+    basePath,
+    ignoreModuleExtensions,
+    builtinModules,
+    resolver_options(options),
+    graph_options(options)
+  );
+}
+
+/**
+ * Walks the same graph as `build_module_graph`, returning it as Graphviz DOT text with the
+ * barrel files classified by `barrelOptions` filled in, dynamic imports dashed and, with
+ * `detectCycles`, the imports of cycles in red.
+ *
+ * @param {string | string[]} entrypoints
+ * @param {{
+ *  amountOfExportsToConsiderModuleAsBarrel: number,
+ *  ignoreTypeExports?: boolean,
+ *  ignoreExportNames?: string[],
+ *  treatStarExportsAsBarrel?: boolean,
+ *  treatNamespaceReexportsAsBarrel?: boolean,
+ *  reexportRatio?: number,
+ * }} barrelOptions
+ * @param {{
+ *  basePath?: string,
+ *  ignoreModuleExtensions?: string[],
+ *  maxDepth?: number,
+ *  exportConditions?: string[],
+ *  mainFields?: string[],
+ *  extensions?: string[],
+ * }} [options]
+ * @returns {string}
+ */
+export function export_module_graph_dot(entrypoints, barrelOptions, options = {}) {
+  const {
+    basePath = process.cwd(),
+    ignoreModuleExtensions = [],
+  } = options;
+
+  return export_module_graph_dot_rs(
+    typeof entrypoints === "string" ? [entrypoints] : entrypoints,
+    basePath,
+    ignoreModuleExtensions,
+    builtinModules,
+    resolver_options(options),
+    graph_options(options),
+    barrelOptions
+  );
+}
+
+/**
+ * Walks the same graph as `build_module_graph`, returning it as a JSON string for tooling
+ * outside of Node. Each of its `nodes` is an `{ id, bytes, imports, importedBy }` object,
+ * `bytes` being `null` for nodes that are not files, such as collapsed packages.
+ *
+ * @param {string | string[]} entrypoints
+ * @param {{
+ *  basePath?: string,
+ *  ignoreModuleExtensions?: string[],
+ *  maxDepth?: number,
+ *  exportConditions?: string[],
+ *  mainFields?: string[],
+ *  extensions?: string[],
+ * }} [options]
+ * @returns {string}
+ */
+export function export_module_graph_json(entrypoints, options = {}) {
+  const {
+    basePath = process.cwd(),
+    ignoreModuleExtensions = [],
+  } = options;
+
+  return export_module_graph_json_rs(
+    typeof entrypoints === "string" ? [entrypoints] : entrypoints,
+    basePath,
+    ignoreModuleExtensions,
+    builtinModules,
+    resolver_options(options),
+    graph_options(options)
+  );
+}
+
+export {
+  is_barrel_file,
+  is_barrel_files,
+  is_barrel_file_path,
+  classify_barrel_file,
+  ModuleGraphCancellation
+};
@@ -292,8 +292,27 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { resolveRs, isBarrelFileRs, countModuleGraphSizeRs } = nativeBinding
+const { resolveRs, resolveFromDirectoryRs, resolveManyRs, resolveWithDetailsRs, resolveAsyncRs, getPackageForPathRs, isBarrelFileRs, isBarrelFilesRs, isBarrelFilePathRs, classifyBarrelFileRs, countModuleGraphSizeRs, countModuleGraphSizeAsyncRs, countModuleGraphModulesRs, countModuleGraphPerEntryRs, buildModuleGraphRs, exportModuleGraphDotRs, exportModuleGraphJsonRs, BarrelAnalyzer, ModuleGraphAnalyzer, ModuleGraphCancellation, ResolverHandle, ModuleGraphWatcher } = nativeBinding
 
 module.exports.resolveRs = resolveRs
+module.exports.resolveFromDirectoryRs = resolveFromDirectoryRs
+module.exports.resolveManyRs = resolveManyRs
+module.exports.resolveWithDetailsRs = resolveWithDetailsRs
+module.exports.resolveAsyncRs = resolveAsyncRs
+module.exports.getPackageForPathRs = getPackageForPathRs
 module.exports.isBarrelFileRs = isBarrelFileRs
+module.exports.isBarrelFilesRs = isBarrelFilesRs
+module.exports.isBarrelFilePathRs = isBarrelFilePathRs
+module.exports.classifyBarrelFileRs = classifyBarrelFileRs
 module.exports.countModuleGraphSizeRs = countModuleGraphSizeRs
+module.exports.countModuleGraphSizeAsyncRs = countModuleGraphSizeAsyncRs
+module.exports.countModuleGraphModulesRs = countModuleGraphModulesRs
+module.exports.countModuleGraphPerEntryRs = countModuleGraphPerEntryRs
+module.exports.buildModuleGraphRs = buildModuleGraphRs
+module.exports.exportModuleGraphDotRs = exportModuleGraphDotRs
+module.exports.exportModuleGraphJsonRs = exportModuleGraphJsonRs
+module.exports.BarrelAnalyzer = BarrelAnalyzer
+module.exports.ModuleGraphAnalyzer = ModuleGraphAnalyzer
+module.exports.ModuleGraphCancellation = ModuleGraphCancellation
+module.exports.ResolverHandle = ResolverHandle
+module.exports.ModuleGraphWatcher = ModuleGraphWatcher
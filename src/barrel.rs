@@ -0,0 +1,1833 @@
+//! Barrel file classification used by `is_barrel_file_rs` and `classify_barrel_file_rs`.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use napi::Status::GenericFailure;
+use napi::{Error, Result};
+
+use napi_derive::napi;
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{
+  Argument, AssignmentExpression, CallExpression, Declaration, Expression, ImportDeclaration,
+  ObjectPropertyKind, Program, Statement, VariableDeclaration,
+};
+use oxc_ecmascript::BoundNames;
+use oxc_parser::{ParseOptions, Parser, ParserReturn};
+use oxc_span::{GetSpan, SourceType, Span};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::lexer::ModuleLexer;
+use crate::sfc::{is_component_file, script_source};
+
+#[napi(object, object_to_js = false)]
+#[derive(Clone, Default)]
+pub struct BarrelFileOptions {
+  /// Files with more exports than this, and fewer local declarations than exports, are barrels
+  pub amount_of_exports_to_consider_module_as_barrel: u32,
+  /// Files with more `export ... from` statements than this are barrels, whatever their
+  /// other exports and declarations, unset by default
+  pub amount_of_reexports_to_consider_module_as_barrel: Option<u32>,
+  /// Leave out type-only exports such as `export type { Foo } from './foo'`, which are
+  /// erased at build time, defaults to `false`
+  pub ignore_type_exports: Option<bool>,
+  /// Export names left out of every count, e.g. `default`, `__esModule` or the `metadata` and
+  /// `config` exports frameworks require of route files
+  pub ignore_export_names: Option<Vec<String>>,
+  /// Classify any file with an `export * from` as a barrel, whatever the thresholds,
+  /// defaults to `false`
+  pub treat_star_exports_as_barrel: Option<bool>,
+  /// Classify any file with an `export * as ns from` as a barrel, whatever the thresholds,
+  /// defaults to `false`
+  pub treat_namespace_reexports_as_barrel: Option<bool>,
+  /// Classify files by the share of their exports that are re-exports, e.g. `0.8`, in place
+  /// of `amount_of_exports_to_consider_module_as_barrel`, each `export * from` counts as one
+  pub reexport_ratio: Option<f64>,
+  /// Classify files by a score weighing each kind of export, in place of the thresholds and
+  /// `reexport_ratio`
+  pub weights: Option<BarrelWeights>,
+  /// A regex matched against `file_path`, e.g. `(^|/)index\.(ts|js)x?$`, whose matches are
+  /// classified with `amount_of_exports_to_consider_matching_file_as_barrel`
+  pub barrel_filename_pattern: Option<String>,
+  /// The export threshold of files matching `barrel_filename_pattern`, which are barrels as
+  /// soon as they re-export anything when unset
+  pub amount_of_exports_to_consider_matching_file_as_barrel: Option<u32>,
+  /// A comment that opts a file out when it comes before any code, e.g. `barrel-file-ok`
+  /// for `/* barrel-file-ok */`, unset by default
+  pub opt_out_comment: Option<String>,
+  /// Accept `return` at the top level, which Node allows in CommonJS modules, defaults to
+  /// `true` for `.cjs` and `.cts` files and `false` otherwise
+  pub allow_return_outside_function: Option<bool>,
+  /// How `.d.ts`, `.d.mts` and `.d.cts` files are classified, defaults to `classify`
+  pub declaration_files: Option<DeclarationFiles>,
+  /// Selects the TypeScript, JSX and CommonJS parsing of the source from its extension,
+  /// `.vue` and `.svelte` files from their `<script>` blocks, unknown or missing extensions
+  /// as plain ES modules
+  pub file_path: Option<String>,
+}
+
+/// What each kind of export adds to a file's `score`.
+#[napi(object, object_to_js = false)]
+#[derive(Clone, Default)]
+pub struct BarrelWeights {
+  /// Each name re-exported by `export { a } from` or `require`, defaults to `1`
+  pub named_reexport: Option<f64>,
+  /// Each `export * from`, defaults to `1`
+  pub star_export: Option<f64>,
+  /// Each `export * as ns from`, defaults to `1`
+  pub namespace_reexport: Option<f64>,
+  /// Each `export { default } from`, defaults to `1`
+  pub default_reexport: Option<f64>,
+  /// Each local declaration, defaults to `-1`
+  pub local_declaration: Option<f64>,
+  /// Files scoring more than this are barrels, defaults to
+  /// `amount_of_exports_to_consider_module_as_barrel`
+  pub threshold: Option<f64>,
+}
+
+#[napi(string_enum)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeclarationFiles {
+  /// Like any other file
+  #[napi(value = "classify")]
+  Classify,
+  /// Never as barrels, they are erased at runtime
+  #[napi(value = "never-barrel")]
+  NeverBarrel,
+  /// As `is_type_barrel` in place of `is_barrel`
+  #[napi(value = "type-barrel")]
+  TypeBarrel,
+}
+
+/// The counts a barrel file classification is based on.
+#[napi(object)]
+#[derive(Clone, Debug)]
+pub struct BarrelClassification {
+  /// Exported names, including names re-exported from other modules and CommonJS
+  /// `exports` properties
+  pub export_count: u32,
+  /// Top level variable, function, class, interface, type alias, enum and namespace
+  /// declarations, exported or not, with one per name a destructuring pattern binds, leaving
+  /// out `require` bindings
+  pub local_declaration_count: u32,
+  /// `export ... from` statements, including `export * from`, and properties of
+  /// `module.exports` assigned a `require` call
+  pub reexport_count: u32,
+  /// `export * from` statements and `require` calls spread into `module.exports`, which
+  /// re-export every name of their module
+  pub star_export_count: u32,
+  /// `export * as ns from` statements, each counted as a single export and re-export though
+  /// they expose every name of their module
+  pub namespace_reexport_count: u32,
+  /// Names of `export { a as b }` lists without a `from` clause that refer to bindings
+  /// declared in the file, which aren't re-exports
+  pub local_reexport_count: u32,
+  /// Names of `export { a }` lists without a `from` clause that refer to imports, which pass
+  /// through like `export { a } from './a'` and count as re-exported names for
+  /// `reexport_ratio`
+  pub imported_reexport_count: u32,
+  /// The sum of the `weights` of the file's exports, with the default weights when unset
+  pub score: f64,
+  pub is_barrel: bool,
+  /// With `declaration_files: "type-barrel"`, whether a declaration file would be a barrel
+  pub is_type_barrel: bool,
+  /// Whether the file only re-exports from a single module, e.g. `export * from './impl'`
+  /// or `export { default } from './impl'`, a pass-through rather than an aggregating barrel
+  pub is_proxy: bool,
+  /// Whether the file runs top level code for its effects, such as polyfill imports or
+  /// registrations, so importing its modules directly would skip those effects
+  pub has_side_effects: bool,
+  /// Whether the file starts with `opt_out_comment`, for rules to leave it alone
+  pub opted_out: bool,
+  /// Why the file is or isn't a barrel, e.g. to show in a lint report
+  pub reason: String,
+  /// A summary of the exports to interpolate into a report message, e.g. `12 of 14 exports
+  /// are re-exports from 9 modules`
+  pub explanation: String,
+  /// Where each re-export counted in `reexport_count` is, in source order
+  pub reexports: Vec<ReexportSpan>,
+  /// The distinct specifiers of the modules re-exported from, in source order, including
+  /// the imports of `imported_reexport_count`, e.g. to suggest direct imports
+  pub reexported_modules: Vec<String>,
+  /// Whether the source parsed without syntax errors, the counts only cover what oxc could
+  /// recover otherwise
+  pub parsed_completely: bool,
+  /// The syntax errors oxc reported
+  pub errors: Vec<ParseDiagnostic>,
+}
+
+#[napi(object)]
+#[derive(Clone, Debug, Serialize)]
+pub struct ParseDiagnostic {
+  pub message: String,
+  /// Byte offset of the start of the offending code
+  pub start: u32,
+  /// Byte offset of the end of the offending code
+  pub end: u32,
+  pub loc: SourceLocation,
+}
+
+/// A re-export statement, or the `require` call of a CommonJS re-export.
+#[napi(object)]
+#[derive(Clone, Debug)]
+pub struct ReexportSpan {
+  /// The module re-exported from, e.g. `./button`
+  pub source: String,
+  /// Byte offset of the start of the re-export
+  pub start: u32,
+  /// Byte offset of the end of the re-export
+  pub end: u32,
+  pub loc: SourceLocation,
+}
+
+/// A range in ESLint's `loc` format.
+#[napi(object)]
+#[derive(Clone, Debug, Serialize)]
+pub struct SourceLocation {
+  pub start: Position,
+  pub end: Position,
+}
+
+#[napi(object)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Position {
+  /// 1-based line
+  pub line: u32,
+  /// 0-based column in UTF-16 code units, as ESLint counts them
+  pub column: u32,
+}
+
+pub fn classify_barrel_file(source: &str, options: &BarrelFileOptions) -> BarrelClassification {
+  let component_script = options
+    .file_path
+    .as_deref()
+    .filter(|file_path| is_component_file(file_path))
+    .map(|_| script_source(source));
+  let (parsed_source, source_type) = match &component_script {
+    Some((script, source_type)) => (script.as_str(), *source_type),
+    None => (source, source_type(options)),
+  };
+  let allocator = Allocator::default();
+  let parse_options = ParseOptions {
+    allow_return_outside_function: options
+      .allow_return_outside_function
+      .unwrap_or(source_type.is_script()),
+    ..ParseOptions::default()
+  };
+  let ret = Parser::new(&allocator, parsed_source, source_type)
+    .with_options(parse_options)
+    .parse();
+  classify_program(&ret, source, source_type, options)
+}
+
+/// Classifies the program of `ret`, already parsed from `source` as `source_type`, for
+/// callers that parse the module for more than its exports.
+pub fn classify_program(
+  ret: &ParserReturn,
+  source: &str,
+  source_type: SourceType,
+  options: &BarrelFileOptions,
+) -> BarrelClassification {
+  let program = &ret.program;
+  let ModuleLexer { mut exports, .. } = ModuleLexer::new().build(program);
+  let ignore_type_exports = options.ignore_type_exports.unwrap_or_default();
+  if ignore_type_exports {
+    exports.retain(|export| !export.t);
+  }
+  let ignored_names = options.ignore_export_names.as_deref().unwrap_or_default();
+  let is_ignored = |name: &str| ignored_names.iter().any(|ignored| ignored == name);
+  exports.retain(|export| !is_ignored(&export.n));
+
+  let mut declarations = 0;
+  let mut reexports = 0;
+  let mut star_exports = 0;
+  let mut namespace_reexports = 0;
+  let mut default_reexports = 0;
+  let mut reexported_names = 0;
+  let mut local_reexports = 0;
+  let mut imported_reexports = 0;
+  let mut reexport_spans = Vec::new();
+  let mut imported_modules = Vec::new();
+  let imported_names: HashMap<&str, &ImportDeclaration> = program
+    .body
+    .iter()
+    .filter_map(|statement| match statement {
+      Statement::ImportDeclaration(import) => Some(import),
+      _ => None,
+    })
+    .flat_map(|import| {
+      import
+        .specifiers
+        .iter()
+        .flatten()
+        .map(move |specifier| (specifier.local().name.as_str(), &**import))
+    })
+    .collect();
+  for declaration in &program.body {
+    match declaration {
+      Statement::VariableDeclaration(variable) => {
+        declarations += bound_name_count(variable);
+      }
+      Statement::FunctionDeclaration(_) => {
+        declarations += 1;
+      }
+      Statement::ClassDeclaration(_) => {
+        declarations += 1;
+      }
+      Statement::TSInterfaceDeclaration(_)
+      | Statement::TSTypeAliasDeclaration(_)
+      | Statement::TSEnumDeclaration(_)
+      | Statement::TSModuleDeclaration(_) => {
+        declarations += 1;
+      }
+      // export const { a, b } = obj, export declare function c(): void
+      Statement::ExportNamedDeclaration(export) if export.declaration.is_some() => {
+        declarations += match &export.declaration {
+          Some(Declaration::VariableDeclaration(variable)) => bound_name_count(variable),
+          _ => 1,
+        };
+      }
+      Statement::ExportNamedDeclaration(export) if export.source.is_some() => {
+        let type_only = export.export_kind.is_type()
+          || (!export.specifiers.is_empty()
+            && export
+              .specifiers
+              .iter()
+              .all(|specifier| specifier.export_kind.is_type()));
+        if !(ignore_type_exports && type_only) {
+          reexports += 1;
+          reexport_spans.extend(
+            export
+              .source
+              .as_ref()
+              .map(|source| (source.value.to_string(), export.span)),
+          );
+        }
+        for specifier in export.specifiers.iter().filter(|specifier| {
+          !(ignore_type_exports
+            && (export.export_kind.is_type() || specifier.export_kind.is_type()))
+            && !is_ignored(&specifier.exported.name())
+        }) {
+          reexported_names += 1;
+          if specifier.local.name() == "default" {
+            default_reexports += 1;
+          }
+        }
+      }
+      // export { a as b }
+      Statement::ExportNamedDeclaration(export) => {
+        for specifier in &export.specifiers {
+          if (ignore_type_exports
+            && (export.export_kind.is_type() || specifier.export_kind.is_type()))
+            || is_ignored(&specifier.exported.name())
+          {
+            continue;
+          }
+          if let Some(import) = imported_names.get(specifier.local.name().as_str()) {
+            imported_reexports += 1;
+            imported_modules.push((import.span.start, import.source.value.as_str()));
+          } else {
+            local_reexports += 1;
+          }
+        }
+      }
+      Statement::ExportAllDeclaration(export)
+        if !(ignore_type_exports && export.export_kind.is_type()) =>
+      {
+        reexports += 1;
+        reexport_spans.push((export.source.value.to_string(), export.span));
+        match &export.exported {
+          None => star_exports += 1,
+          Some(exported) if !is_ignored(&exported.name()) => {
+            reexported_names += 1;
+            namespace_reexports += 1;
+          }
+          Some(_) => {}
+        }
+      }
+      _ => {}
+    }
+  }
+
+  let mut commonjs = CommonJsExports::from_program(program, ignored_names);
+  let export_count = exports.len() + commonjs.exports;
+  reexports += commonjs.reexports;
+  star_exports += commonjs.star_exports;
+  reexported_names += commonjs.reexported_names + imported_reexports;
+  reexport_spans.append(&mut commonjs.reexport_spans);
+  reexport_spans.sort_by_key(|(_, span)| span.start);
+  imported_modules.extend(
+    reexport_spans
+      .iter()
+      .map(|(module, span)| (span.start, module.as_str())),
+  );
+  imported_modules.sort_by_key(|(start, _)| *start);
+  let mut reexported_modules: Vec<String> = Vec::new();
+  for (_, module) in imported_modules {
+    if !reexported_modules
+      .iter()
+      .any(|reexported| reexported == module)
+    {
+      reexported_modules.push(module.to_string());
+    }
+  }
+  let ratio = match export_count + star_exports as usize {
+    0 => 0.0,
+    total => (reexported_names + star_exports as usize) as f64 / total as f64,
+  };
+
+  let matches_filename_pattern = options.matches_barrel_filename();
+  let matching_threshold = options
+    .amount_of_exports_to_consider_matching_file_as_barrel
+    .filter(|_| matches_filename_pattern);
+  let threshold =
+    matching_threshold.unwrap_or(options.amount_of_exports_to_consider_module_as_barrel) as usize;
+  let matches_barrel_filename =
+    matches_filename_pattern && matching_threshold.is_none() && reexports > 0;
+  let weights = options.weights.clone().unwrap_or_default();
+  let score = (reexported_names - namespace_reexports as usize - default_reexports) as f64
+    * weights.named_reexport.unwrap_or(1.0)
+    + star_exports as f64 * weights.star_export.unwrap_or(1.0)
+    + namespace_reexports as f64 * weights.namespace_reexport.unwrap_or(1.0)
+    + default_reexports as f64 * weights.default_reexport.unwrap_or(1.0)
+    + declarations as f64 * weights.local_declaration.unwrap_or(-1.0);
+  let score_threshold = weights.threshold.unwrap_or(threshold as f64);
+  let reexport_threshold = options.amount_of_reexports_to_consider_module_as_barrel;
+  let too_many_reexports = reexport_threshold.is_some_and(|threshold| reexports > threshold);
+  let has_star_exports =
+    options.treat_star_exports_as_barrel.unwrap_or_default() && star_exports > 0;
+  let mostly_reexports = match (&options.weights, options.reexport_ratio) {
+    (Some(_), _) => score > score_threshold,
+    (None, Some(reexport_ratio)) => ratio > reexport_ratio,
+    (None, None) => declarations < export_count && export_count > threshold,
+  };
+  let has_namespace_reexports = options
+    .treat_namespace_reexports_as_barrel
+    .unwrap_or_default()
+    && namespace_reexports > 0;
+  let mut is_barrel = matches_barrel_filename
+    || has_star_exports
+    || has_namespace_reexports
+    || too_many_reexports
+    || mostly_reexports;
+  let mut is_type_barrel = false;
+  let declaration_files = source_type.is_typescript_definition().then(|| {
+    options
+      .declaration_files
+      .unwrap_or(DeclarationFiles::Classify)
+  });
+  let reason = if declaration_files == Some(DeclarationFiles::NeverBarrel) {
+    is_barrel = false;
+    "declaration files are erased at runtime".to_string()
+  } else if matches_barrel_filename {
+    format!(
+      "the file name matches the barrel pattern and it has {} re-exports",
+      reexports
+    )
+  } else if has_star_exports {
+    format!("star re-exports ({}) always make a barrel", star_exports)
+  } else if has_namespace_reexports {
+    format!(
+      "namespace re-exports ({}) always make a barrel",
+      namespace_reexports
+    )
+  } else if too_many_reexports {
+    format!(
+      "{} re-exports, more than the threshold of {}",
+      reexports,
+      reexport_threshold.unwrap_or_default()
+    )
+  } else if options.weights.is_some() {
+    format!(
+      "a score of {}, {} the threshold of {}",
+      score,
+      if is_barrel {
+        "more than"
+      } else {
+        "not more than"
+      },
+      score_threshold
+    )
+  } else if let Some(reexport_ratio) = options.reexport_ratio {
+    format!(
+      "{:.0}% of exports are re-exports, {} the ratio of {:.0}%",
+      ratio * 100.0,
+      if is_barrel {
+        "more than"
+      } else {
+        "not more than"
+      },
+      reexport_ratio * 100.0
+    )
+  } else if export_count <= threshold {
+    format!(
+      "{} exports, not more than the threshold of {}",
+      export_count, threshold
+    )
+  } else if is_barrel {
+    format!(
+      "{} exports, more than the threshold of {}, but only {} local declarations",
+      export_count, threshold, declarations
+    )
+  } else {
+    format!(
+      "{} exports, but as many local declarations ({})",
+      export_count, declarations
+    )
+  };
+  if declaration_files == Some(DeclarationFiles::TypeBarrel) {
+    (is_barrel, is_type_barrel) = (false, is_barrel);
+  }
+
+  let explanation = explanation(
+    reexported_names + star_exports as usize,
+    export_count + star_exports as usize,
+    reexported_modules.len(),
+  );
+  let line_index = LineIndex::new(source);
+  BarrelClassification {
+    export_count: export_count as u32,
+    local_declaration_count: declarations as u32,
+    reexport_count: reexports,
+    star_export_count: star_exports,
+    namespace_reexport_count: namespace_reexports,
+    local_reexport_count: local_reexports as u32,
+    imported_reexport_count: imported_reexports as u32,
+    score,
+    is_barrel,
+    is_type_barrel,
+    is_proxy: is_proxy(program),
+    has_side_effects: program.body.iter().any(is_side_effect),
+    opted_out: options
+      .opt_out_comment
+      .as_deref()
+      .is_some_and(|opt_out_comment| has_leading_comment(program, opt_out_comment)),
+    reason,
+    explanation,
+    reexports: reexport_spans
+      .into_iter()
+      .map(|(module, span)| ReexportSpan {
+        source: module,
+        start: span.start,
+        end: span.end,
+        loc: line_index.location(span),
+      })
+      .collect(),
+    reexported_modules,
+    parsed_completely: !ret.panicked && ret.errors.is_empty(),
+    errors: parse_diagnostics(ret, &line_index),
+  }
+}
+
+/// The syntax errors of a parse of `source`, located in ESLint's format.
+pub fn source_parse_diagnostics(source: &str, ret: &ParserReturn) -> Vec<ParseDiagnostic> {
+  parse_diagnostics(ret, &LineIndex::new(source))
+}
+
+fn parse_diagnostics(ret: &ParserReturn, line_index: &LineIndex) -> Vec<ParseDiagnostic> {
+  ret
+    .errors
+    .iter()
+    .map(|error| {
+      let span = error
+        .labels
+        .as_ref()
+        .and_then(|labels| labels.first())
+        .map_or(Span::default(), |label| {
+          Span::new(label.offset() as u32, (label.offset() + label.len()) as u32)
+        });
+      ParseDiagnostic {
+        message: error.message.to_string(),
+        start: span.start,
+        end: span.end,
+        loc: line_index.location(span),
+      }
+    })
+    .collect()
+}
+
+/// Maps byte offsets of a source to ESLint positions.
+struct LineIndex<'a> {
+  source: &'a str,
+  line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+  fn new(source: &'a str) -> Self {
+    let line_starts = std::iter::once(0)
+      .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+      .collect();
+    Self {
+      source,
+      line_starts,
+    }
+  }
+
+  fn position(&self, offset: u32) -> Position {
+    let offset = (offset as usize).min(self.source.len());
+    let line = self
+      .line_starts
+      .partition_point(|line_start| *line_start <= offset);
+    let line_start = self.line_starts[line - 1];
+    Position {
+      line: line as u32,
+      column: self.source[line_start..offset].encode_utf16().count() as u32,
+    }
+  }
+
+  fn location(&self, span: Span) -> SourceLocation {
+    SourceLocation {
+      start: self.position(span.start),
+      end: self.position(span.end),
+    }
+  }
+}
+
+impl BarrelFileOptions {
+  /// The compiled `barrel_filename_pattern`, compiled by the first classification that needs
+  /// it and shared by those after, so classifying many files doesn't compile it for each.
+  pub fn barrel_filename_regex(&self) -> Result<Option<Arc<Regex>>> {
+    static BARREL_FILENAME_REGEXES: LazyLock<Mutex<HashMap<String, Arc<Regex>>>> =
+      LazyLock::new(Mutex::default);
+    let Some(pattern) = &self.barrel_filename_pattern else {
+      return Ok(None);
+    };
+    let mut regexes = BARREL_FILENAME_REGEXES.lock().unwrap();
+    if let Some(regex) = regexes.get(pattern) {
+      return Ok(Some(Arc::clone(regex)));
+    }
+    let regex = Arc::new(Regex::new(pattern).map_err(|error| {
+      Error::new(
+        GenericFailure,
+        format!("Invalid barrel_filename_pattern, message: \"{}\"", error),
+      )
+    })?);
+    regexes.insert(pattern.clone(), Arc::clone(&regex));
+    Ok(Some(regex))
+  }
+
+  /// Whether `file_path` matches `barrel_filename_pattern`.
+  fn matches_barrel_filename(&self) -> bool {
+    self
+      .barrel_filename_regex()
+      .ok()
+      .flatten()
+      .zip(self.file_path.as_ref())
+      .is_some_and(|(pattern, file_path)| pattern.is_match(&file_path.replace('\\', "/")))
+  }
+}
+
+/// Summarizes how many of a file's exports are re-exports, for rule messages.
+fn explanation(reexports: usize, exports: usize, modules: usize) -> String {
+  let plural = |count: usize, word: &str| match count {
+    1 => format!("{count} {word}"),
+    _ => format!("{count} {word}s"),
+  };
+  match (reexports, exports) {
+    (_, 0) => "no exports".to_string(),
+    (0, 1) => "the only export isn't a re-export".to_string(),
+    (0, _) => format!("none of the {} exports are re-exports", exports),
+    (1, _) => format!(
+      "1 of {} is a re-export from {}",
+      plural(exports, "export"),
+      plural(modules, "module")
+    ),
+    _ => format!(
+      "{} of {} are re-exports from {}",
+      reexports,
+      plural(exports, "export"),
+      plural(modules, "module")
+    ),
+  }
+}
+
+fn source_type(options: &BarrelFileOptions) -> SourceType {
+  options
+    .file_path
+    .as_ref()
+    .and_then(|file_path| SourceType::from_path(file_path).ok())
+    .unwrap_or_default()
+}
+
+/// Reads and classifies the file at `path`, which also selects how it is parsed unless
+/// `options.file_path` is set. A UTF-8 BOM is left out like ESLint leaves it out of
+/// `sourceCode.text`, so offsets and positions match it.
+pub fn classify_barrel_file_path(
+  path: &str,
+  options: &BarrelFileOptions,
+) -> std::io::Result<BarrelClassification> {
+  let source = std::fs::read_to_string(path)?;
+  let source = source.strip_prefix('\u{feff}').unwrap_or(&source);
+  let options = BarrelFileOptions {
+    file_path: options.file_path.clone().or_else(|| Some(path.to_string())),
+    ..options.clone()
+  };
+  Ok(classify_barrel_file(source, &options))
+}
+
+/// Classifies `(path, source)` pairs on as many threads as there are cores, the path of
+/// each pair selecting how its source is parsed.
+pub fn classify_barrel_files(
+  files: &[(String, String)],
+  options: &BarrelFileOptions,
+) -> Vec<BarrelClassification> {
+  let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+  let chunk_size = files.len().div_ceil(threads).max(1);
+  std::thread::scope(|scope| {
+    let chunks: Vec<_> = files
+      .chunks(chunk_size)
+      .map(|chunk| {
+        scope.spawn(move || {
+          chunk
+            .iter()
+            .map(|(path, source)| {
+              let options = BarrelFileOptions {
+                file_path: Some(path.clone()),
+                ..options.clone()
+              };
+              classify_barrel_file(source, &options)
+            })
+            .collect::<Vec<_>>()
+        })
+      })
+      .collect();
+    chunks
+      .into_iter()
+      .flat_map(|chunk| chunk.join().unwrap())
+      .collect()
+  })
+}
+
+/// The exports of CommonJS modules, read from top level `module.exports` and `exports`
+/// assignments, `Object.assign(module.exports, ...)` and `Object.defineProperty(exports, ...)`
+/// calls, and the star re-export helpers TypeScript and Babel compile `export *` to.
+struct CommonJsExports<'p, 'a> {
+  ignored_names: &'p [String],
+  /// Top level `var a = require('./a')` bindings, which compiled re-exports refer to
+  requires: HashMap<&'p str, &'p CallExpression<'a>>,
+  exports: usize,
+  reexports: u32,
+  star_exports: u32,
+  reexported_names: usize,
+  reexport_spans: Vec<(String, Span)>,
+}
+
+impl<'p, 'a> CommonJsExports<'p, 'a> {
+  fn from_program(program: &'p Program<'a>, ignored_names: &'p [String]) -> Self {
+    let requires = program
+      .body
+      .iter()
+      .filter_map(|statement| match statement {
+        Statement::VariableDeclaration(variable) => Some(&variable.declarations),
+        _ => None,
+      })
+      .flatten()
+      .filter_map(|declarator| {
+        let name = declarator.id.get_binding_identifier()?.name.as_str();
+        Some((name, required_module(declarator.init.as_ref()?)?))
+      })
+      .collect();
+    let mut commonjs = Self {
+      ignored_names,
+      requires,
+      exports: 0,
+      reexports: 0,
+      star_exports: 0,
+      reexported_names: 0,
+      reexport_spans: Vec::new(),
+    };
+    for statement in &program.body {
+      let Statement::ExpressionStatement(statement) = statement else {
+        continue;
+      };
+      match statement.expression.get_inner_expression() {
+        // exports.b = exports.a = void 0, declaring the exports assigned further down
+        Expression::AssignmentExpression(assignment) if is_hoisted_export(assignment) => {}
+        Expression::AssignmentExpression(assignment) => {
+          let Some(target) = assignment.left.as_member_expression() else {
+            continue;
+          };
+          if target.is_specific_member_access("module", "exports") {
+            // module.exports = { ...require('./a'), b: require('./b') }
+            commonjs.add_object(&assignment.right);
+          } else if is_exports_object(target.object())
+            && let Some(name) = target.static_property_name()
+          {
+            // exports.a = require('./a')
+            commonjs.add_property(Some(name), &assignment.right);
+          }
+        }
+        Expression::CallExpression(call) => commonjs.add_call(call),
+        _ => {}
+      }
+    }
+    commonjs
+  }
+
+  fn add_call(&mut self, call: &'p CallExpression<'a>) {
+    let mut arguments = call.arguments.iter().filter_map(Argument::as_expression);
+    if call.callee.is_specific_member_access("Object", "assign") {
+      // Object.assign(module.exports, require('./a'), { b })
+      if arguments.next().is_some_and(is_exports_object) {
+        arguments.for_each(|argument| self.add_object(argument));
+      }
+    } else if call
+      .callee
+      .is_specific_member_access("Object", "defineProperty")
+    {
+      // Object.defineProperty(exports, "a", { enumerable: true, get: function () { return a_1.a; } })
+      if arguments.next().is_some_and(is_exports_object)
+        && let Some(Expression::StringLiteral(name)) = arguments.next()
+        && let Some(descriptor) = arguments.next()
+      {
+        let value = descriptor_value(descriptor).unwrap_or(descriptor);
+        self.add_property(Some(name.value.as_str()), value);
+      }
+    } else if let Some(module) = star_export_helper(call).or_else(|| keys_loop(call))
+      && let Some(require) = self.require_of(module)
+    {
+      self.add_reexport(require);
+      self.star_exports += 1;
+    }
+  }
+
+  /// Adds an object whose properties all become exports.
+  fn add_object(&mut self, expression: &'p Expression<'a>) {
+    let expression = expression.get_inner_expression();
+    if let Some(require) = self.require_of(expression) {
+      self.add_reexport(require);
+      self.star_exports += 1;
+      return;
+    }
+    match expression {
+      Expression::ObjectExpression(object) => {
+        for property in &object.properties {
+          match property {
+            ObjectPropertyKind::ObjectProperty(property) => {
+              self.add_property(property.key.static_name().as_deref(), &property.value)
+            }
+            ObjectPropertyKind::SpreadProperty(spread) => self.add_object(&spread.argument),
+          }
+        }
+      }
+      _ => self.exports += 1,
+    }
+  }
+
+  /// Adds a single export, re-exported when its value is a `require` call, unless `name` is
+  /// ignored.
+  fn add_property(&mut self, name: Option<&str>, value: &'p Expression<'a>) {
+    if name.is_some_and(|name| self.ignored_names.iter().any(|ignored| ignored == name)) {
+      return;
+    }
+    self.exports += 1;
+    let value = match value.get_inner_expression() {
+      // require('./a').a
+      Expression::StaticMemberExpression(member) => &member.object,
+      value => value,
+    };
+    if let Some(require) = self.require_of(value) {
+      self.add_reexport(require);
+      self.reexported_names += 1;
+    }
+  }
+
+  fn add_reexport(&mut self, require: &CallExpression) {
+    self.reexports += 1;
+    if let Some(source) = require.common_js_require() {
+      self
+        .reexport_spans
+        .push((source.value.to_string(), require.span));
+    }
+  }
+
+  /// The `require` call `expression` is, or the one the binding it names was assigned.
+  fn require_of(&self, expression: &'p Expression<'a>) -> Option<&'p CallExpression<'a>> {
+    match expression.get_inner_expression() {
+      Expression::Identifier(identifier) => self.requires.get(identifier.name.as_str()).copied(),
+      // __importDefault(a_1)
+      Expression::CallExpression(call) if let Some(argument) = interop_argument(call) => {
+        self.require_of(argument)
+      }
+      expression => require_call(expression),
+    }
+  }
+}
+
+/// The names `variable` declares, counting each name of a destructuring pattern, leaving
+/// out `require` bindings, which are imports rather than declarations.
+fn bound_name_count(variable: &VariableDeclaration) -> usize {
+  let mut count = 0;
+  variable
+    .declarations
+    .iter()
+    .filter(|declarator| declarator.init.as_ref().and_then(required_module).is_none())
+    .for_each(|declarator| declarator.id.bound_names(&mut |_| count += 1));
+  count
+}
+
+/// `expression` as a `require('./a')` call.
+fn require_call<'a, 'b>(expression: &'b Expression<'a>) -> Option<&'b CallExpression<'a>> {
+  match expression {
+    Expression::CallExpression(call) if call.is_require_call() => Some(call),
+    _ => None,
+  }
+}
+
+/// `expression` as a `require('./a')` call, or the one wrapped in an interop helper such as
+/// `__importStar(require('./a'))` or `_interopRequireDefault(require('./a'))`.
+fn required_module<'a, 'b>(expression: &'b Expression<'a>) -> Option<&'b CallExpression<'a>> {
+  let expression = expression.get_inner_expression();
+  match expression {
+    Expression::CallExpression(call) => match interop_argument(call) {
+      Some(argument) => require_call(argument.get_inner_expression()),
+      None => require_call(expression),
+    },
+    _ => None,
+  }
+}
+
+/// The module an interop helper call such as `__importDefault(a_1)` wraps.
+fn interop_argument<'a, 'b>(call: &'b CallExpression<'a>) -> Option<&'b Expression<'a>> {
+  match callee_name(&call.callee)? {
+    "__importStar" | "__importDefault" | "_interopRequireWildcard" | "_interopRequireDefault" => {
+      call.arguments.first()?.as_expression()
+    }
+    _ => None,
+  }
+}
+
+/// The module TypeScript's `__exportStar(require('./a'), exports)` or older
+/// `__export(require('./a'))` helpers re-export.
+fn star_export_helper<'a, 'b>(call: &'b CallExpression<'a>) -> Option<&'b Expression<'a>> {
+  match callee_name(&call.callee)? {
+    "__exportStar" | "__export" => call.arguments.first()?.as_expression(),
+    _ => None,
+  }
+}
+
+/// The module Babel's `Object.keys(_a).forEach(function (key) { ... })` loop re-exports.
+fn keys_loop<'a, 'b>(call: &'b CallExpression<'a>) -> Option<&'b Expression<'a>> {
+  let Expression::StaticMemberExpression(member) = call.callee.get_inner_expression() else {
+    return None;
+  };
+  match member.object.get_inner_expression() {
+    Expression::CallExpression(keys)
+      if member.property.name == "forEach"
+        && keys.callee.is_specific_member_access("Object", "keys") =>
+    {
+      keys.arguments.first()?.as_expression()
+    }
+    _ => None,
+  }
+}
+
+/// The name of a called helper, whether it is called directly, as `tslib.__exportStar` or
+/// as `(0, tslib_1.__exportStar)`.
+fn callee_name<'b>(callee: &'b Expression) -> Option<&'b str> {
+  match callee.get_inner_expression() {
+    Expression::Identifier(identifier) => Some(identifier.name.as_str()),
+    Expression::StaticMemberExpression(member) => Some(member.property.name.as_str()),
+    Expression::SequenceExpression(sequence) => sequence.expressions.last().and_then(callee_name),
+    _ => None,
+  }
+}
+
+/// The exported value of an `Object.defineProperty` descriptor, its `value` or what its
+/// `get` accessor returns.
+fn descriptor_value<'a, 'b>(descriptor: &'b Expression<'a>) -> Option<&'b Expression<'a>> {
+  let Expression::ObjectExpression(object) = descriptor.get_inner_expression() else {
+    return None;
+  };
+  object.properties.iter().find_map(|property| {
+    let ObjectPropertyKind::ObjectProperty(property) = property else {
+      return None;
+    };
+    let body = match (property.key.static_name()?.as_ref(), &property.value) {
+      ("value", value) => return Some(value),
+      ("get", Expression::FunctionExpression(function)) => function.body.as_ref()?,
+      ("get", Expression::ArrowFunctionExpression(arrow)) => &arrow.body,
+      _ => return None,
+    };
+    match body.statements.first()? {
+      Statement::ReturnStatement(statement) => statement.argument.as_ref(),
+      // () => a_1.a
+      Statement::ExpressionStatement(statement) => Some(&statement.expression),
+      _ => None,
+    }
+  })
+}
+
+/// Whether `assignment` only declares exports ahead of their definitions, as TypeScript's
+/// `exports.b = exports.a = void 0` does.
+fn is_hoisted_export(assignment: &AssignmentExpression) -> bool {
+  match assignment.right.get_inner_expression() {
+    Expression::AssignmentExpression(right) => is_hoisted_export(right),
+    right => right.is_void_0(),
+  }
+}
+
+/// Whether `statement` only runs for its effects: side effect imports, control flow and
+/// expression statements other than CommonJS exports.
+fn is_side_effect(statement: &Statement) -> bool {
+  match statement {
+    // import './polyfill'
+    Statement::ImportDeclaration(import) => {
+      !import.import_kind.is_type()
+        && import
+          .specifiers
+          .as_ref()
+          .is_none_or(|specifiers| specifiers.is_empty())
+    }
+    Statement::ExpressionStatement(statement) => {
+      !is_commonjs_export(statement.expression.get_inner_expression())
+    }
+    Statement::BlockStatement(_)
+    | Statement::IfStatement(_)
+    | Statement::SwitchStatement(_)
+    | Statement::ForStatement(_)
+    | Statement::ForInStatement(_)
+    | Statement::ForOfStatement(_)
+    | Statement::WhileStatement(_)
+    | Statement::DoWhileStatement(_)
+    | Statement::TryStatement(_)
+    | Statement::ThrowStatement(_)
+    | Statement::LabeledStatement(_)
+    | Statement::WithStatement(_) => true,
+    _ => false,
+  }
+}
+
+/// Whether `expression` is one of the `module.exports` and `exports` assignments or the
+/// calls `CommonJsExports` reads.
+fn is_commonjs_export(expression: &Expression) -> bool {
+  match expression {
+    Expression::AssignmentExpression(assignment) => assignment
+      .left
+      .as_member_expression()
+      .is_some_and(|target| {
+        target.is_specific_member_access("module", "exports") || is_exports_object(target.object())
+      }),
+    Expression::CallExpression(call) => {
+      ((call.callee.is_specific_member_access("Object", "assign")
+        || call
+          .callee
+          .is_specific_member_access("Object", "defineProperty"))
+        && call
+          .arguments
+          .first()
+          .and_then(Argument::as_expression)
+          .is_some_and(is_exports_object))
+        || star_export_helper(call).is_some()
+        || keys_loop(call).is_some()
+    }
+    _ => false,
+  }
+}
+
+/// Whether a comment before the first directive or statement of `program` reads `comment`,
+/// optionally followed by a description as in `/* barrel-file-ok -- re-exports icons */`.
+fn has_leading_comment(program: &Program, comment: &str) -> bool {
+  let code_start = program
+    .directives
+    .first()
+    .map(|directive| directive.span.start)
+    .into_iter()
+    .chain(program.body.first().map(|statement| statement.span().start))
+    .min()
+    .unwrap_or(program.span.end);
+  program
+    .comments
+    .iter()
+    .take_while(|leading| leading.span.end <= code_start)
+    .any(|leading| {
+      leading
+        .content_span()
+        .source_text(program.source_text)
+        .split_whitespace()
+        .next()
+        == Some(comment)
+    })
+}
+
+/// Whether every statement of `program` re-exports from the same module, directives aside.
+fn is_proxy(program: &Program) -> bool {
+  let mut sources = program.body.iter().map(|statement| match statement {
+    Statement::ExportNamedDeclaration(export) => export.source.as_ref().map(|source| &source.value),
+    Statement::ExportAllDeclaration(export) => Some(&export.source.value),
+    // module.exports = require('./impl')
+    Statement::ExpressionStatement(statement) => {
+      match statement.expression.get_inner_expression() {
+        Expression::AssignmentExpression(assignment)
+          if assignment
+            .left
+            .as_member_expression()
+            .is_some_and(|target| target.is_specific_member_access("module", "exports")) =>
+        {
+          require_call(assignment.right.get_inner_expression())
+            .and_then(|require| require.common_js_require())
+            .map(|source| &source.value)
+        }
+        _ => None,
+      }
+    }
+    _ => None,
+  });
+  match sources.next() {
+    Some(Some(first)) => sources.all(|source| source == Some(first)),
+    _ => false,
+  }
+}
+
+/// `module.exports` or its `exports` shorthand.
+fn is_exports_object(expression: &Expression) -> bool {
+  expression.is_specific_id("exports") || expression.is_specific_member_access("module", "exports")
+}
+
+/// Classifies files with the options it was constructed with, parsing each distinct source
+/// only once so a barrel imported from many files isn't parsed again for every importer.
+#[napi]
+pub struct BarrelAnalyzer {
+  options: BarrelFileOptions,
+  cache: Mutex<ParseCache>,
+}
+
+#[derive(Default)]
+struct ParseCache {
+  /// Classifications by a hash of the source, how it was parsed and how its path matched
+  classifications: HashMap<u64, BarrelClassification>,
+  hits: u32,
+  misses: u32,
+}
+
+#[napi]
+impl BarrelAnalyzer {
+  #[napi(constructor)]
+  pub fn new(options: BarrelFileOptions) -> Result<Self> {
+    options.barrel_filename_regex()?;
+    Ok(Self {
+      options,
+      cache: Mutex::default(),
+    })
+  }
+
+  /// `file_path` takes the place of `options.file_path`.
+  #[napi]
+  pub fn classify(&self, source: String, file_path: Option<String>) -> BarrelClassification {
+    let options = BarrelFileOptions {
+      file_path: file_path.or_else(|| self.options.file_path.clone()),
+      ..self.options.clone()
+    };
+    // Everything the classification reads from `file_path`, files that only differ in their
+    // directory still share an entry
+    let mut hasher = DefaultHasher::new();
+    source_type(&options).hash(&mut hasher);
+    options.matches_barrel_filename().hash(&mut hasher);
+    options
+      .file_path
+      .as_deref()
+      .is_some_and(is_component_file)
+      .hash(&mut hasher);
+    source.hash(&mut hasher);
+    let key = hasher.finish();
+
+    if let Some(classification) = self.cache.lock().unwrap().hit(key) {
+      return classification;
+    }
+    let classification = classify_barrel_file(&source, &options);
+    let mut cache = self.cache.lock().unwrap();
+    cache.misses += 1;
+    cache.classifications.insert(key, classification.clone());
+    classification
+  }
+
+  /// Like `classify`, but reads the file at `path` instead of taking its source.
+  #[napi]
+  pub fn classify_path(&self, path: String) -> Result<BarrelClassification> {
+    let source = std::fs::read_to_string(&path).map_err(|error| {
+      Error::new(
+        GenericFailure,
+        format!("Failed to read file: \"{}\", message: \"{}\"", path, error),
+      )
+    })?;
+    let source = match source.strip_prefix('\u{feff}') {
+      Some(source) => source.to_string(),
+      None => source,
+    };
+    Ok(self.classify(source, Some(path)))
+  }
+
+  #[napi]
+  pub fn cache_stats(&self) -> ParseCacheStats {
+    let cache = self.cache.lock().unwrap();
+    ParseCacheStats {
+      hits: cache.hits,
+      misses: cache.misses,
+      entries: cache.classifications.len() as u32,
+    }
+  }
+
+  #[napi]
+  pub fn clear_cache(&self) {
+    *self.cache.lock().unwrap() = ParseCache::default();
+  }
+}
+
+impl ParseCache {
+  fn hit(&mut self, key: u64) -> Option<BarrelClassification> {
+    let classification = self.classifications.get(&key).cloned()?;
+    self.hits += 1;
+    Some(classification)
+  }
+}
+
+#[napi(object)]
+pub struct ParseCacheStats {
+  /// Classifications answered from the cache
+  pub hits: u32,
+  /// Sources that had to be parsed
+  pub misses: u32,
+  /// Distinct sources currently cached
+  pub entries: u32,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_classify_parses_by_extension() {
+    let source = "const version: string = '1';\nexport { a } from './a';\nexport { b } from './b';\nexport { c } from './c';\n";
+    let mut options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 2,
+      file_path: Some("index.ts".to_string()),
+      ..BarrelFileOptions::default()
+    };
+
+    assert!(classify_barrel_file(source, &options).is_barrel);
+    options.file_path = None;
+    assert!(!classify_barrel_file(source, &options).is_barrel);
+  }
+
+  #[test]
+  fn test_classify_counts_reexports() {
+    let source = "export * from './a';\nexport * as b from './b';\nexport { c, d } from './c';\nexport const e = 1;\n";
+    let classification = classify_barrel_file(
+      source,
+      &BarrelFileOptions {
+        amount_of_exports_to_consider_module_as_barrel: 2,
+        ..BarrelFileOptions::default()
+      },
+    );
+
+    assert_eq!(classification.export_count, 4);
+    assert_eq!(classification.local_declaration_count, 1);
+    assert_eq!(classification.reexport_count, 3);
+    assert_eq!(classification.star_export_count, 1);
+    assert!(classification.is_barrel);
+    assert_eq!(
+      classification.reason,
+      "4 exports, more than the threshold of 2, but only 1 local declarations"
+    );
+  }
+
+  #[test]
+  fn test_classify_reexport_threshold() {
+    let source = "export * from './a';\nexport * from './b';\nexport function c() {}\n";
+    let mut options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 10,
+      ..BarrelFileOptions::default()
+    };
+    assert!(!classify_barrel_file(source, &options).is_barrel);
+
+    options.amount_of_reexports_to_consider_module_as_barrel = Some(1);
+    let classification = classify_barrel_file(source, &options);
+    assert!(classification.is_barrel);
+    assert_eq!(
+      classification.reason,
+      "2 re-exports, more than the threshold of 1"
+    );
+  }
+
+  #[test]
+  fn test_classify_ignores_type_exports() {
+    let source = "export type { A } from './a';\nexport { type B, type C } from './b';\nexport type * from './d';\nexport { e } from './e';\n";
+    let mut options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 2,
+      file_path: Some("index.ts".to_string()),
+      ..BarrelFileOptions::default()
+    };
+    let classification = classify_barrel_file(source, &options);
+    assert!(classification.is_barrel);
+    assert_eq!(classification.reexport_count, 4);
+
+    options.ignore_type_exports = Some(true);
+    let classification = classify_barrel_file(source, &options);
+    assert!(!classification.is_barrel);
+    assert_eq!(classification.export_count, 1);
+    assert_eq!(classification.reexport_count, 1);
+    assert_eq!(classification.star_export_count, 0);
+  }
+
+  #[test]
+  fn test_classify_counts_typescript_declarations() {
+    let source =
+      "interface A {}\ntype B = string;\nenum C {}\nnamespace D {}\nexport { A, B, C, D };\n";
+    let classification = classify_barrel_file(
+      source,
+      &BarrelFileOptions {
+        amount_of_exports_to_consider_module_as_barrel: 2,
+        file_path: Some("index.ts".to_string()),
+        ..BarrelFileOptions::default()
+      },
+    );
+
+    assert_eq!(classification.local_declaration_count, 4);
+    assert!(!classification.is_barrel);
+  }
+
+  #[test]
+  fn test_classify_commonjs_barrels() {
+    let options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 1,
+      file_path: Some("index.cjs".to_string()),
+      ..BarrelFileOptions::default()
+    };
+
+    let source =
+      "module.exports = { ...require('./a'), b: require('./b'), c: require('./c').c };\n";
+    let classification = classify_barrel_file(source, &options);
+    assert_eq!(classification.export_count, 2);
+    assert_eq!(classification.reexport_count, 3);
+    assert_eq!(classification.star_export_count, 1);
+    assert!(classification.is_barrel);
+
+    let source = "exports.a = require('./a');\nmodule.exports.b = require('./b');\nObject.assign(module.exports, require('./c'));\n";
+    let classification = classify_barrel_file(source, &options);
+    assert_eq!(classification.export_count, 2);
+    assert_eq!(classification.reexport_count, 3);
+    assert_eq!(classification.star_export_count, 1);
+    assert!(classification.is_barrel);
+
+    let source = "function a() {}\nfunction b() {}\nmodule.exports = { a, b };\n";
+    assert!(!classify_barrel_file(source, &options).is_barrel);
+  }
+
+  #[test]
+  fn test_classify_star_exports_as_barrel() {
+    let source = "export * from './a';\nexport function b() {}\n";
+    let mut options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 10,
+      ..BarrelFileOptions::default()
+    };
+    assert!(!classify_barrel_file(source, &options).is_barrel);
+
+    options.treat_star_exports_as_barrel = Some(true);
+    let classification = classify_barrel_file(source, &options);
+    assert!(classification.is_barrel);
+    assert_eq!(
+      classification.reason,
+      "star re-exports (1) always make a barrel"
+    );
+    assert!(!classify_barrel_file("export function b() {}\n", &options).is_barrel);
+  }
+
+  #[test]
+  fn test_classify_reexport_ratio() {
+    let source = "export { a, b, c } from './a';\nexport * from './d';\nexport function e() {}\n";
+    let mut options = BarrelFileOptions {
+      reexport_ratio: Some(0.7),
+      ..BarrelFileOptions::default()
+    };
+    let classification = classify_barrel_file(source, &options);
+    assert!(classification.is_barrel);
+    assert_eq!(
+      classification.reason,
+      "80% of exports are re-exports, more than the ratio of 70%"
+    );
+
+    options.reexport_ratio = Some(0.8);
+    assert!(!classify_barrel_file(source, &options).is_barrel);
+  }
+
+  #[test]
+  fn test_classify_barrel_files_keeps_order() {
+    let barrel = "export { a } from './a';\nexport { b } from './b';\n";
+    let files: Vec<_> = (0..16)
+      .map(|index| match index % 2 {
+        0 => (format!("{index}.ts"), barrel.to_string()),
+        _ => (
+          format!("{index}.ts"),
+          "export const a: number = 1;\n".to_string(),
+        ),
+      })
+      .collect();
+    let classifications = classify_barrel_files(
+      &files,
+      &BarrelFileOptions {
+        amount_of_exports_to_consider_module_as_barrel: 1,
+        ..BarrelFileOptions::default()
+      },
+    );
+
+    assert_eq!(classifications.len(), files.len());
+    for (index, classification) in classifications.iter().enumerate() {
+      assert_eq!(classification.is_barrel, index % 2 == 0);
+    }
+  }
+
+  #[test]
+  fn test_classify_barrel_file_path() {
+    let fixture = format!("{}/fixtures/barrel", env!("CARGO_MANIFEST_DIR"));
+    let options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 1,
+      ..BarrelFileOptions::default()
+    };
+
+    let classification = classify_barrel_file_path(&format!("{fixture}/index.ts"), &options);
+    assert!(classification.unwrap().is_barrel);
+    assert!(classify_barrel_file_path(&format!("{fixture}/missing.ts"), &options).is_err());
+
+    let bom = format!("{}/fixtures/shebang/bom.js", env!("CARGO_MANIFEST_DIR"));
+    let reexports = classify_barrel_file_path(&bom, &options).unwrap().reexports;
+    assert_eq!(reexports[0].start, 0);
+    assert_eq!(reexports[0].loc.start, Position { line: 1, column: 0 });
+  }
+
+  #[test]
+  fn test_classify_reexport_spans() {
+    let source = "// ünïcode\nexport { a } from './a';\nconst b = require('./b');\nexports.ü = require('./c');\n";
+    let classification = classify_barrel_file(source, &BarrelFileOptions::default());
+
+    let reexports = &classification.reexports;
+    assert_eq!(reexports.len(), 2);
+    assert_eq!(reexports[0].source, "./a");
+    assert_eq!(
+      &source[reexports[0].start as usize..reexports[0].end as usize],
+      "export { a } from './a';"
+    );
+    assert_eq!(reexports[0].loc.start, Position { line: 2, column: 0 });
+    assert_eq!(
+      reexports[0].loc.end,
+      Position {
+        line: 2,
+        column: 24
+      }
+    );
+    assert_eq!(reexports[1].source, "./c");
+    assert_eq!(
+      reexports[1].loc.start,
+      Position {
+        line: 4,
+        column: 12
+      }
+    );
+  }
+
+  #[test]
+  fn test_classify_side_effects() {
+    let options = BarrelFileOptions::default();
+    let classify = |source| classify_barrel_file(source, &options).has_side_effects;
+
+    assert!(!classify(
+      "import { a } from './a';\nexport { a };\nexport * from './b';\n"
+    ));
+    assert!(!classify(
+      "module.exports = { ...require('./a') };\nexports.b = 1;\n"
+    ));
+    assert!(classify("import './polyfill';\nexport * from './a';\n"));
+    assert!(classify("register('button');\nexport * from './a';\n"));
+    assert!(classify("if (typeof window !== 'undefined') {}\n"));
+  }
+
+  #[test]
+  fn test_classify_reports_syntax_errors() {
+    let options = BarrelFileOptions::default();
+    let classification = classify_barrel_file("export { a } from './a';\n", &options);
+    assert!(classification.parsed_completely);
+    assert!(classification.errors.is_empty());
+
+    let classification = classify_barrel_file("export { a } from './a';\nconst = 1;\n", &options);
+    assert!(!classification.parsed_completely);
+    assert_eq!(classification.errors.len(), 1);
+    assert_eq!(classification.errors[0].loc.start.line, 2);
+  }
+
+  #[test]
+  fn test_classify_parses_modern_syntax() {
+    let sources = [
+      (
+        "index.ts",
+        "@register export class A { @observable x = 1 }\n",
+      ),
+      (
+        "index.js",
+        "using a = open();\nawait using b = connect();\n",
+      ),
+      (
+        "index.js",
+        "import config from './config.json' with { type: 'json' };\n",
+      ),
+      (
+        "index.cjs",
+        "if (module.parent) return;\nmodule.exports = require('./a');\n",
+      ),
+    ];
+    for (file_path, source) in sources {
+      let source = format!("{source}export {{ b }} from './b';\n");
+      let classification = classify_barrel_file(
+        &source,
+        &BarrelFileOptions {
+          file_path: Some(file_path.to_string()),
+          ..BarrelFileOptions::default()
+        },
+      );
+      assert!(classification.parsed_completely, "{source}");
+      assert!(classification.reexport_count >= 1, "{source}");
+    }
+  }
+
+  #[test]
+  fn test_classify_declaration_files() {
+    let source = "export declare function a(): void;\nexport declare const b: string, c: number;\nexport { d } from './d';\nexport { e } from './e';\n";
+    let mut options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 1,
+      file_path: Some("index.d.ts".to_string()),
+      ..BarrelFileOptions::default()
+    };
+    let classification = classify_barrel_file(source, &options);
+    assert_eq!(classification.local_declaration_count, 3);
+    assert!(classification.is_barrel);
+
+    options.declaration_files = Some(DeclarationFiles::NeverBarrel);
+    let classification = classify_barrel_file(source, &options);
+    assert!(!classification.is_barrel);
+    assert!(!classification.is_type_barrel);
+
+    options.declaration_files = Some(DeclarationFiles::TypeBarrel);
+    let classification = classify_barrel_file(source, &options);
+    assert!(!classification.is_barrel);
+    assert!(classification.is_type_barrel);
+  }
+
+  #[test]
+  fn test_classify_ignore_export_names() {
+    let source = "export const metadata = {};\nexport const config = {};\nexport { a } from './a';\nexport { default } from './page';\n";
+    let mut options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 2,
+      ..BarrelFileOptions::default()
+    };
+    let classification = classify_barrel_file(source, &options);
+    assert_eq!(classification.export_count, 4);
+    assert!(classification.is_barrel);
+
+    options.ignore_export_names = Some(vec![
+      "metadata".to_string(),
+      "config".to_string(),
+      "default".to_string(),
+    ]);
+    let classification = classify_barrel_file(source, &options);
+    assert_eq!(classification.export_count, 1);
+    assert!(!classification.is_barrel);
+
+    options.ignore_export_names = Some(vec!["__esModule".to_string()]);
+    let classification = classify_barrel_file(
+      "exports.__esModule = true;\nmodule.exports = { __esModule: true, a: require('./a') };\n",
+      &options,
+    );
+    assert_eq!(classification.export_count, 1);
+  }
+
+  #[test]
+  fn test_classify_destructuring_exports() {
+    let options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 2,
+      ..BarrelFileOptions::default()
+    };
+    let classification =
+      classify_barrel_file("export const { a, b: [c, { d }], ...e } = obj;\n", &options);
+    assert_eq!(classification.export_count, 4);
+    assert_eq!(classification.local_declaration_count, 4);
+    assert!(!classification.is_barrel);
+
+    let classification =
+      classify_barrel_file("const { a, b, c } = obj;\nexport { a, b, c };\n", &options);
+    assert_eq!(classification.local_declaration_count, 3);
+    assert!(!classification.is_barrel);
+  }
+
+  #[test]
+  fn test_classify_local_reexports() {
+    let source =
+      "import { a } from './a';\nconst b = 1;\nexport { a, b as c };\nexport { d } from './d';\n";
+    let options = BarrelFileOptions {
+      reexport_ratio: Some(0.5),
+      ..BarrelFileOptions::default()
+    };
+    let classification = classify_barrel_file(source, &options);
+    assert_eq!(classification.local_reexport_count, 1);
+    assert_eq!(classification.imported_reexport_count, 1);
+    assert_eq!(classification.reexport_count, 1);
+    assert!(classification.is_barrel);
+    assert_eq!(
+      classification.reason,
+      "67% of exports are re-exports, more than the ratio of 50%"
+    );
+  }
+
+  #[test]
+  fn test_barrel_analyzer_cache() {
+    let analyzer = BarrelAnalyzer::new(BarrelFileOptions::default()).unwrap();
+    let source = "export * from './a';\n";
+
+    analyzer.classify(source.to_string(), Some("a/index.ts".to_string()));
+    analyzer.classify(source.to_string(), Some("b/index.ts".to_string()));
+    analyzer.classify(source.to_string(), Some("index.js".to_string()));
+    let stats = analyzer.cache_stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 2));
+
+    analyzer.clear_cache();
+    assert_eq!(analyzer.cache_stats().entries, 0);
+
+    // Only files matching the pattern get its threshold, and components parse their script
+    let analyzer = BarrelAnalyzer::new(BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 5,
+      barrel_filename_pattern: Some("(^|/)index\\.ts$".to_string()),
+      amount_of_exports_to_consider_matching_file_as_barrel: Some(0),
+      ..BarrelFileOptions::default()
+    })
+    .unwrap();
+    let source = "export { a } from './a';\nexport { b } from './b';\n";
+    let classify =
+      |file_path: &str| analyzer.classify(source.to_string(), Some(file_path.to_string()));
+    assert!(classify("index.ts").is_barrel);
+    assert!(!classify("a.ts").is_barrel);
+    assert!(classify("b/index.ts").is_barrel);
+    assert!(!classify("index.vue").is_barrel);
+    assert_eq!(analyzer.cache_stats().misses, 3);
+
+    analyzer.clear_cache();
+    assert_eq!(analyzer.cache_stats().entries, 0);
+  }
+
+  #[test]
+  fn test_classify_proxy() {
+    let options = BarrelFileOptions::default();
+    let is_proxy = |source| classify_barrel_file(source, &options).is_proxy;
+
+    assert!(is_proxy("export * from './impl';\n"));
+    assert!(is_proxy(
+      "'use client';\nexport { default } from './impl';\nexport * from './impl';\n"
+    ));
+    assert!(is_proxy("module.exports = require('./impl');\n"));
+    assert!(!is_proxy("export * from './a';\nexport * from './b';\n"));
+    assert!(!is_proxy("export * from './impl';\nexport const a = 1;\n"));
+    assert!(!is_proxy(""));
+  }
+
+  #[test]
+  fn test_classify_namespace_reexports() {
+    let source = "export * as utils from './utils';\nexport function a() {}\n";
+    let mut options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 5,
+      ..BarrelFileOptions::default()
+    };
+    let classification = classify_barrel_file(source, &options);
+    assert_eq!(classification.namespace_reexport_count, 1);
+    assert_eq!(classification.star_export_count, 0);
+    assert!(!classification.is_barrel);
+
+    options.treat_namespace_reexports_as_barrel = Some(true);
+    let classification = classify_barrel_file(source, &options);
+    assert!(classification.is_barrel);
+    assert_eq!(
+      classification.reason,
+      "namespace re-exports (1) always make a barrel"
+    );
+  }
+
+  #[test]
+  fn test_classify_compiled_commonjs_barrels() {
+    let options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 1,
+      file_path: Some("index.js".to_string()),
+      ..BarrelFileOptions::default()
+    };
+
+    // tsc --module commonjs
+    let source = r#""use strict";
+Object.defineProperty(exports, "__esModule", { value: true });
+exports.b = exports.a = void 0;
+const tslib_1 = require("tslib");
+tslib_1.__exportStar(require("./icons"), exports);
+var a_1 = require("./a");
+Object.defineProperty(exports, "a", { enumerable: true, get: function () { return a_1.a; } });
+var b_1 = require("./b");
+Object.defineProperty(exports, "b", { enumerable: true, get: function () { return __importDefault(b_1).default; } });
+"#;
+    let classification = classify_barrel_file(source, &options);
+    assert_eq!(classification.export_count, 3);
+    assert_eq!(classification.local_declaration_count, 0);
+    assert_eq!(classification.reexport_count, 3);
+    assert_eq!(classification.star_export_count, 1);
+    assert!(classification.is_barrel);
+    assert!(!classification.has_side_effects);
+
+    // babel --plugins @babel/plugin-transform-modules-commonjs
+    let source = r#""use strict";
+Object.defineProperty(exports, "__esModule", { value: true });
+var _icons = require("./icons");
+Object.keys(_icons).forEach(function (key) {
+  if (key === "default" || key === "__esModule") return;
+  Object.defineProperty(exports, key, { enumerable: true, get: function () { return _icons[key]; } });
+});
+"#;
+    let classification = classify_barrel_file(source, &options);
+    assert_eq!(classification.reexport_count, 1);
+    assert_eq!(classification.star_export_count, 1);
+    assert_eq!(classification.reexports[0].source, "./icons");
+    assert!(!classification.has_side_effects);
+  }
+
+  #[test]
+  fn test_classify_components() {
+    let source = "<template>\n  <div />\n</template>\n<script lang=\"ts\">\nexport { default as Button } from './Button.vue';\nexport type { Size } from './types';\n</script>\n";
+    let classification = classify_barrel_file(
+      source,
+      &BarrelFileOptions {
+        amount_of_exports_to_consider_module_as_barrel: 1,
+        file_path: Some("index.vue".to_string()),
+        ..BarrelFileOptions::default()
+      },
+    );
+    assert!(classification.parsed_completely);
+    assert!(classification.is_barrel);
+    assert_eq!(classification.reexports[0].loc.start.line, 5);
+
+    let source =
+      "<script context=\"module\">\n  export { load } from './load';\n</script>\n\n<p>{data}</p>\n";
+    let classification = classify_barrel_file(
+      source,
+      &BarrelFileOptions {
+        file_path: Some("+page.svelte".to_string()),
+        ..BarrelFileOptions::default()
+      },
+    );
+    assert!(classification.parsed_completely);
+    assert_eq!(classification.reexported_modules, ["./load"]);
+  }
+
+  #[test]
+  fn test_classify_reexported_modules() {
+    let source = "import { a } from './a';\nexport { b } from './b';\nexport { a };\nexport * from './c';\nexport { d } from './b';\nmodule.exports.e = require('./e');\n";
+    let classification = classify_barrel_file(source, &BarrelFileOptions::default());
+    assert_eq!(
+      classification.reexported_modules,
+      ["./a", "./b", "./c", "./e"]
+    );
+  }
+
+  #[test]
+  fn test_classify_weights() {
+    let source = "export { default } from './page';\nexport { a, b } from './a';\nexport * from './c';\nexport function d() {}\n";
+    let mut options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 10,
+      ..BarrelFileOptions::default()
+    };
+    let classification = classify_barrel_file(source, &options);
+    assert_eq!(classification.score, 3.0);
+    assert!(!classification.is_barrel);
+
+    options.weights = Some(BarrelWeights {
+      star_export: Some(5.0),
+      default_reexport: Some(0.0),
+      threshold: Some(4.0),
+      ..BarrelWeights::default()
+    });
+    let classification = classify_barrel_file(source, &options);
+    assert_eq!(classification.score, 6.0);
+    assert!(classification.is_barrel);
+    assert_eq!(
+      classification.reason,
+      "a score of 6, more than the threshold of 4"
+    );
+  }
+
+  #[test]
+  fn test_classify_barrel_filename_pattern() {
+    let source = "export { a } from './a';\nexport function b() {}\nexport function c() {}\n";
+    let mut options = BarrelFileOptions {
+      amount_of_exports_to_consider_module_as_barrel: 10,
+      barrel_filename_pattern: Some(r"(^|/)index\.(ts|js)x?$".to_string()),
+      file_path: Some("src\\components\\index.ts".to_string()),
+      ..BarrelFileOptions::default()
+    };
+    let classification = classify_barrel_file(source, &options);
+    assert!(classification.is_barrel);
+    assert_eq!(
+      classification.reason,
+      "the file name matches the barrel pattern and it has 1 re-exports"
+    );
+
+    options.amount_of_exports_to_consider_matching_file_as_barrel = Some(2);
+    assert_eq!(
+      classify_barrel_file(source, &options).reason,
+      "3 exports, more than the threshold of 2, but only 2 local declarations"
+    );
+
+    options.file_path = Some("src/components/button.ts".to_string());
+    options.amount_of_exports_to_consider_matching_file_as_barrel = None;
+    assert!(!classify_barrel_file(source, &options).is_barrel);
+    // The pattern is compiled once for every classification
+    assert!(Arc::ptr_eq(
+      &options.barrel_filename_regex().unwrap().unwrap(),
+      &options.clone().barrel_filename_regex().unwrap().unwrap()
+    ));
+
+    options.barrel_filename_pattern = Some("(".to_string());
+    assert!(BarrelAnalyzer::new(options).is_err());
+  }
+
+  #[test]
+  fn test_classify_opt_out_comment() {
+    let mut options = BarrelFileOptions::default();
+    let source = "#!/usr/bin/env node\n/* barrel-file-ok -- re-exports icons */\n'use client';\nexport * from './icons';\n";
+    assert!(!classify_barrel_file(source, &options).opted_out);
+
+    options.opt_out_comment = Some("barrel-file-ok".to_string());
+    assert!(classify_barrel_file(source, &options).opted_out);
+    assert!(classify_barrel_file("// barrel-file-ok\n", &options).opted_out);
+    assert!(
+      !classify_barrel_file("export * from './icons';\n/* barrel-file-ok */\n", &options).opted_out
+    );
+    assert!(
+      !classify_barrel_file(
+        "/* barrel-file-ok-not */\nexport * from './icons';\n",
+        &options
+      )
+      .opted_out
+    );
+  }
+
+  #[test]
+  fn test_classify_explanation() {
+    let options = BarrelFileOptions::default();
+    let explain = |source| classify_barrel_file(source, &options).explanation;
+
+    assert_eq!(
+      explain("export { a, b } from './a';\nexport * from './c';\nexport const d = 1;\n"),
+      "3 of 4 exports are re-exports from 2 modules"
+    );
+    assert_eq!(
+      explain("export { a } from './a';\n"),
+      "1 of 1 export is a re-export from 1 module"
+    );
+    assert_eq!(
+      explain("export const a = 1;\n"),
+      "the only export isn't a re-export"
+    );
+    assert_eq!(explain("const a = 1;\n"), "no exports");
+  }
+}
@@ -0,0 +1,114 @@
+//! Glob patterns for the module graph's `exclude` and `include` options, matched against `/`
+//! separated paths relative to `base_path`.
+
+use napi::Status::GenericFailure;
+use napi::{Error, Result};
+use regex::RegexSet;
+
+/// A set of globs supporting `*`, `**`, `?`, `[...]` classes and `{a,b}` alternatives.
+pub struct GlobSet(RegexSet);
+
+impl GlobSet {
+  /// `option` names the option the patterns came from in the error for an invalid pattern.
+  pub fn new(patterns: &[String], option: &str) -> Result<Self> {
+    RegexSet::new(patterns.iter().map(|pattern| glob_to_regex(pattern)))
+      .map(Self)
+      .map_err(|error| {
+        Error::new(
+          GenericFailure,
+          format!("Invalid {} pattern, message: {}", option, error),
+        )
+      })
+  }
+
+  pub fn is_match(&self, path: &str) -> bool {
+    self.0.is_match(&path.replace('\\', "/"))
+  }
+
+  /// The index of the first pattern matching `path`, in the order the patterns were given.
+  pub fn first_match(&self, path: &str) -> Option<usize> {
+    self.0.matches(&path.replace('\\', "/")).iter().next()
+  }
+}
+
+fn glob_to_regex(pattern: &str) -> String {
+  let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+  let mut regex = String::from("^");
+  let mut chars = pattern.chars().peekable();
+  let mut alternatives = 0;
+  while let Some(char) = chars.next() {
+    match char {
+      '*' if chars.peek() == Some(&'*') => {
+        chars.next();
+        // `**/` also matches no directories at all
+        if chars.peek() == Some(&'/') {
+          chars.next();
+          regex.push_str("(?:.*/)?");
+        } else {
+          regex.push_str(".*");
+        }
+      }
+      '*' => regex.push_str("[^/]*"),
+      '?' => regex.push_str("[^/]"),
+      '[' => {
+        regex.push('[');
+        if chars.peek() == Some(&'!') {
+          chars.next();
+          regex.push('^');
+        }
+        // An unclosed class stays unclosed, for `RegexSet::new` to reject
+        for char in chars.by_ref() {
+          if char == ']' {
+            regex.push(']');
+            break;
+          }
+          if char == '\\' {
+            regex.push('\\');
+          }
+          regex.push(char);
+        }
+      }
+      '{' => {
+        alternatives += 1;
+        regex.push_str("(?:");
+      }
+      ',' if alternatives > 0 => regex.push('|'),
+      '}' if alternatives > 0 => {
+        alternatives -= 1;
+        regex.push(')');
+      }
+      char => regex.push_str(&regex::escape(&char.to_string())),
+    }
+  }
+  regex.push('$');
+  regex
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_glob_set_matches() {
+    let globs = GlobSet::new(
+      &[
+        "**/*.test.ts".to_string(),
+        "**/__mocks__/**".to_string(),
+        "./src/*.{js,mjs}".to_string(),
+      ],
+      "exclude",
+    )
+    .unwrap();
+
+    assert!(globs.is_match("button.test.ts"));
+    assert!(globs.is_match("src/components/button.test.ts"));
+    assert!(globs.is_match("src/__mocks__/fs.js"));
+    assert!(globs.is_match("src\\index.mjs"));
+    assert!(!globs.is_match("src/button.ts"));
+    assert!(!globs.is_match("src/nested/index.js"));
+    assert_eq!(globs.first_match("src/__mocks__/fs.test.ts"), Some(0));
+    assert_eq!(globs.first_match("src/index.js"), Some(2));
+    assert_eq!(globs.first_match("src/button.ts"), None);
+    assert!(GlobSet::new(&["[a-".to_string()], "exclude").is_err());
+  }
+}
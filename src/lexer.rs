@@ -6,6 +6,7 @@ use oxc_ast::{Visit, ast::*, visit::walk};
 use oxc_ecmascript::BoundNames;
 use oxc_span::{Atom, GetSpan};
 
+/// Mirrors es-module-lexer's output, the fields this crate doesn't read yet allow dead code.
 #[derive(Debug, Clone)]
 pub struct ImportSpecifier<'a> {
   /// Module name
@@ -16,9 +17,11 @@ pub struct ImportSpecifier<'a> {
   pub n: Option<Atom<'a>>,
 
   /// Start of module specifier
+  #[allow(dead_code)]
   pub s: u32,
 
   /// End of module specifier
+  #[allow(dead_code)]
   pub e: u32,
 
   /// Start of import statement
@@ -31,30 +34,38 @@ pub struct ImportSpecifier<'a> {
   pub d: ImportType,
 
   /// If this import has an import assertion, this is the start value
+  #[allow(dead_code)]
   pub a: Option<u32>,
 
-  /// If this import is for types only
+  /// If this import is for types only, including imports of only `type` specifiers which
+  /// TypeScript elides
   pub t: bool,
 }
 
+/// Like `ImportSpecifier`, mirrors es-module-lexer's output.
 #[derive(Debug, Clone)]
 pub struct ExportSpecifier<'a> {
   /// Exported name
   pub n: Atom<'a>,
 
   /// Local name, or undefined.
+  #[allow(dead_code)]
   pub ln: Option<Atom<'a>>,
 
   /// Start of exported name
+  #[allow(dead_code)]
   pub s: u32,
 
   /// End of exported name
+  #[allow(dead_code)]
   pub e: u32,
 
   /// Start of local name
+  #[allow(dead_code)]
   pub ls: Option<u32>,
 
   /// End of local name
+  #[allow(dead_code)]
   pub le: Option<u32>,
 
   /// If this export is for types only
@@ -75,6 +86,7 @@ pub enum ImportType {
 }
 
 impl ImportType {
+  #[allow(dead_code)]
   pub fn as_dynamic_import(&self) -> Option<u32> {
     match self {
       Self::DynamicImport(start) => Some(*start),
@@ -217,7 +229,14 @@ impl<'a> Visit<'a> for ModuleLexer<'a> {
       se: decl.span.end,
       d: ImportType::StaticImport,
       a: assertions,
-      t: decl.import_kind.is_type(),
+      // import { type a } from 'foo'
+      t: decl.import_kind.is_type()
+        || decl.specifiers.as_ref().is_some_and(|specifiers| {
+          !specifiers.is_empty()
+            && specifiers.iter().all(|specifier| {
+              matches!(specifier, ImportDeclarationSpecifier::ImportSpecifier(specifier) if specifier.import_kind.is_type())
+            })
+        }),
     });
     walk::walk_import_declaration(self, decl);
   }
@@ -233,7 +252,13 @@ impl<'a> Visit<'a> for ModuleLexer<'a> {
         se: decl.span.end,
         d: ImportType::StaticImport,
         a: None,
-        t: decl.export_kind.is_type(),
+        // export { type named } from 'foo'
+        t: decl.export_kind.is_type()
+          || (!decl.specifiers.is_empty()
+            && decl
+              .specifiers
+              .iter()
+              .all(|specifier| specifier.export_kind.is_type())),
       });
     }
 
@@ -270,7 +295,8 @@ impl<'a> Visit<'a> for ModuleLexer<'a> {
         e: exported_end,
         ls: Some(s.local.span().start),
         le: Some(s.local.span().end),
-        t: decl.export_kind.is_type(),
+        // export type { named } / export { type named }
+        t: decl.export_kind.is_type() || s.export_kind.is_type(),
       }
     }));
     walk::walk_export_named_declaration(self, decl);
@@ -1,22 +1,37 @@
 use napi::Status::GenericFailure;
+use napi::bindgen_prelude::AsyncTask;
 use napi::{Env, Error, Result};
 use napi_derive::napi;
-use oxc_allocator::Allocator;
-use oxc_ast::ast::Statement;
-use oxc_parser::Parser;
-use oxc_resolver::{AliasValue, ResolveOptions, Resolver, TsconfigOptions, TsconfigReferences};
-use oxc_span::SourceType;
-use pathdiff::diff_paths;
 use regex::Regex;
-use std::collections::HashSet;
-use std::path::PathBuf;
-
-use crate::lexer::ModuleLexer;
 
+use crate::barrel::{
+  BarrelClassification, BarrelFileOptions, classify_barrel_file, classify_barrel_file_path,
+  classify_barrel_files,
+};
+use crate::module_graph::{
+  CountModuleGraphTask, ModuleGraph, ModuleGraphBreakdown, ModuleGraphOptions, ModuleGraphSize,
+  count_module_graph_per_entry, count_module_graph_size, module_graph_dot, module_graph_json,
+  walk_module_graph,
+};
+use crate::resolver::{
+  PackageInfo, ResolutionDetails, ResolveManyResult, ResolveTask, ResolverOptions, create_resolver,
+  is_url_specifier, resolve_from_directory, resolve_importee, resolve_importee_details,
+  resolve_importees,
+};
+
+mod barrel;
+mod glob;
 mod lexer;
+mod module_graph;
+mod resolver;
+mod sfc;
+mod watch;
 
 pub fn is_bare_module_specifier(specifier: &str) -> bool {
   let specifier = specifier.replace('\'', "");
+  if is_url_specifier(&specifier) {
+    return false;
+  }
   if let Some(first_char) = specifier.chars().next() {
     let re = Regex::new(r"[@a-zA-Z]").unwrap();
     return re.is_match(&first_char.to_string());
@@ -26,109 +41,127 @@ pub fn is_bare_module_specifier(specifier: &str) -> bool {
 
 #[napi]
 pub fn resolve_rs(
-  _env: Env,
+  env: Env,
   importer: String,
   importee: String,
-  condition_names: Vec<String>,
-  main_fields: Vec<String>,
-  extensions: Vec<String>,
-  tsconfig_config_file: Option<String>,
-  tsconfig_references: Option<Vec<String>>,
+  options: ResolverOptions,
 ) -> Result<String> {
-  let tsconfig = match tsconfig_config_file {
-    None => None,
-    _ => Some(create_tsconfig_option(
-      tsconfig_config_file.unwrap(),
-      tsconfig_references,
-    )),
-  };
-  let options: ResolveOptions = ResolveOptions {
-    tsconfig,
-    condition_names,
-    main_fields,
-    extensions,
-    ..ResolveOptions::default()
-  };
-  let resolver = Resolver::new(options);
+  let resolver = create_resolver(options)?;
+  resolve_importee(&resolver, &importer, &importee).map_err(|error| error.into_napi_error(&env))
+}
 
-  let importer_path = PathBuf::from(&importer);
-  let importer_parent = importer_path.parent().unwrap().to_str().unwrap();
+/// Like `resolve_rs`, but resolves as if imported from a file inside `context_dir`.
+#[napi]
+pub fn resolve_from_directory_rs(
+  env: Env,
+  context_dir: String,
+  importee: String,
+  options: ResolverOptions,
+) -> Result<String> {
+  let resolver = create_resolver(options)?;
+  resolve_from_directory(&resolver, &context_dir, &importee)
+    .map_err(|error| error.into_napi_error(&env))
+}
 
-  let resolved_url = match resolver.resolve(importer_parent, &importee) {
-    Ok(url) => url,
-    Err(_) => {
-      return Err(Error::new(
-        GenericFailure,
-        format!(
-          "Failed to resolve importer: \"{}\", importee: \"{}\"",
-          &importer, &importee
-        ),
-      ));
-    }
-  };
-  Ok(resolved_url.path().to_str().unwrap().to_string())
+/// Resolves every importee against the same importer with a single resolver,
+/// so the resolver's cache is shared across the whole batch.
+#[napi]
+pub fn resolve_many_rs(
+  _env: Env,
+  importer: String,
+  importees: Vec<String>,
+  options: ResolverOptions,
+) -> Result<Vec<ResolveManyResult>> {
+  let resolver = create_resolver(options)?;
+  Ok(resolve_importees(&resolver, &importer, &importees))
+}
+
+/// Like `resolve_rs`, but returns the package and query information of the resolution
+/// alongside the path.
+#[napi]
+pub fn resolve_with_details_rs(
+  env: Env,
+  importer: String,
+  importee: String,
+  options: ResolverOptions,
+) -> Result<ResolutionDetails> {
+  let resolver = create_resolver(options)?;
+  resolve_importee_details(&resolver, &importer, &importee)
+    .map_err(|error| error.into_napi_error(&env))
+}
+
+/// Resolves on the libuv thread pool instead of blocking the main thread.
+#[napi]
+pub fn resolve_async_rs(
+  _env: Env,
+  importer: String,
+  importee: String,
+  options: ResolverOptions,
+) -> AsyncTask<ResolveTask> {
+  AsyncTask::new(ResolveTask::new(options, importer, importee))
+}
+
+/// Finds the `package.json` owning `path`, returning its `name` and `version`.
+#[napi]
+pub fn get_package_for_path_rs(_env: Env, path: String) -> Result<Option<PackageInfo>> {
+  let resolver = create_resolver(ResolverOptions::default())?;
+  Ok(resolver.package_for_path(&path))
 }
 
+/// `file_path` selects the TypeScript, JSX and CommonJS parsing of `source` from its
+/// extension, unknown or missing extensions are parsed as plain ES modules.
 #[napi]
 pub fn is_barrel_file_rs(
   _env: Env,
   source: String,
   amount_of_exports_to_consider_module_as_barrel: u32,
+  file_path: Option<String>,
 ) -> Result<bool> {
-  let allocator = Allocator::default();
-  let ret = Parser::new(&allocator, &source, SourceType::default()).parse();
-  let ModuleLexer { exports, .. } = ModuleLexer::new().build(&ret.program);
-
-  let mut declarations = 0;
-  for declaration in ret.program.body {
-    match declaration {
-      Statement::VariableDeclaration(variable) => {
-        declarations += variable.declarations.len();
-      }
-      Statement::FunctionDeclaration(_) => {
-        declarations += 1;
-      }
-      Statement::ClassDeclaration(_) => {
-        declarations += 1;
-      }
-      _ => {}
-    }
-  }
-
-  if declarations < exports.len()
-    && exports.len() > amount_of_exports_to_consider_module_as_barrel as usize
-  {
-    return Ok(true);
-  }
-  Ok(false)
+  let options = BarrelFileOptions {
+    amount_of_exports_to_consider_module_as_barrel,
+    file_path,
+    ..BarrelFileOptions::default()
+  };
+  Ok(classify_barrel_file(&source, &options).is_barrel)
 }
 
-fn create_tsconfig_option(
-  config_file: String,
-  tsconfig_references: Option<Vec<String>>,
-) -> TsconfigOptions {
-  TsconfigOptions {
-    config_file: PathBuf::from(config_file),
-    references: match tsconfig_references {
-      None => TsconfigReferences::Auto,
-      Some(refs) => TsconfigReferences::Paths(refs.into_iter().map(PathBuf::from).collect()),
-    },
-  }
+/// Classifies `(path, source)` pairs in parallel, the path of each pair taking the place of
+/// `options.file_path`.
+#[napi]
+pub fn is_barrel_files_rs(
+  _env: Env,
+  files: Vec<(String, String)>,
+  options: BarrelFileOptions,
+) -> Result<Vec<BarrelClassification>> {
+  options.barrel_filename_regex()?;
+  Ok(classify_barrel_files(&files, &options))
 }
 
-fn create_alias_option(aliases: Vec<(String, Vec<String>)>) -> Vec<(String, Vec<AliasValue>)> {
-  aliases
-    .into_iter()
-    .map(|(key, val)| {
-      let mapped_alias = if val.is_empty() {
-        vec![AliasValue::Ignore]
-      } else {
-        val.into_iter().map(AliasValue::Path).collect()
-      };
+/// Like `classify_barrel_file_rs`, but reads the file at `path` instead of taking its source.
+#[napi]
+pub fn is_barrel_file_path_rs(
+  _env: Env,
+  path: String,
+  options: BarrelFileOptions,
+) -> Result<BarrelClassification> {
+  options.barrel_filename_regex()?;
+  classify_barrel_file_path(&path, &options).map_err(|error| {
+    Error::new(
+      GenericFailure,
+      format!("Failed to read file: \"{}\", message: \"{}\"", path, error),
+    )
+  })
+}
 
-      (key, mapped_alias)
-    })
-    .collect()
+/// Like `is_barrel_file_rs`, but returns the counts the classification is based on.
+#[napi]
+pub fn classify_barrel_file_rs(
+  _env: Env,
+  source: String,
+  options: BarrelFileOptions,
+) -> Result<BarrelClassification> {
+  options.barrel_filename_regex()?;
+  Ok(classify_barrel_file(&source, &options))
 }
 
 #[napi]
@@ -136,123 +169,163 @@ pub fn count_module_graph_size_rs(
   _env: Env,
   entry_points: Vec<String>,
   base_path: String,
-  condition_names: Vec<String>,
-  main_fields: Vec<String>,
-  extensions: Vec<String>,
   ignore_module_extensions: Vec<String>,
   builtin_modules: Vec<String>,
-  tsconfig_config_file: Option<String>,
-  tsconfig_references: Option<Vec<String>>,
-  alias: Vec<(String, Vec<String>)>,
+  options: ResolverOptions,
+  graph_options: Option<ModuleGraphOptions>,
 ) -> Result<i32> {
-  let tsconfig = match tsconfig_config_file {
-    None => None,
-    _ => Some(create_tsconfig_option(
-      tsconfig_config_file.unwrap(),
-      tsconfig_references,
-    )),
-  };
-
-  let alias_options = create_alias_option(alias);
-
-  let options = ResolveOptions {
-    condition_names,
-    main_fields,
-    extensions,
-    tsconfig,
-    alias: alias_options,
-    ..ResolveOptions::default()
-  };
-  let mut visited_modules = HashSet::new();
-  let mut modules = Vec::new();
-
-  let resolver = Resolver::new(options);
-
-  for file_path in &entry_points {
-    let resolved_url = resolver.resolve(&base_path, file_path).unwrap();
-    let module_path = diff_paths(resolved_url.full_path(), &base_path).unwrap();
-
-    modules.push(module_path);
-  }
-
-  while let Some(dep) = modules.pop() {
-    let allocator = Allocator::default();
-    let path = PathBuf::from(&base_path).join(&dep);
-
-    let module_extension = path.extension().unwrap().to_str().unwrap();
-
-    if ignore_module_extensions.contains(&module_extension.to_string()) {
-      continue;
-    }
-
-    let source = match std::fs::read_to_string(PathBuf::from(&base_path).join(&dep)) {
-      Ok(source) => source,
-      Err(_) => {
-        return Err(Error::new(
-          GenericFailure,
-          format!("Failed to read file: \"{}{}\"", &base_path, &dep.display()),
-        ));
-      }
-    };
-
-    let source_type = SourceType::from_path(PathBuf::from(&base_path).join(&dep)).unwrap();
-    let ret = Parser::new(&allocator, &source, source_type).parse();
-    let ModuleLexer { imports, .. } = ModuleLexer::new().build(&ret.program);
-
-    visited_modules.insert(dep.to_str().unwrap().to_string());
-
-    for import in imports {
-      if import.n.is_none() {
-        continue;
-      }
+  let resolver = create_resolver(options)?;
+  count_module_graph_size(
+    &entry_points,
+    &base_path,
+    &ignore_module_extensions,
+    &builtin_modules,
+    &resolver,
+    &graph_options.unwrap_or_default(),
+  )
+}
 
-      if import.t {
-        continue;
-      }
-      let importee = import.n.unwrap().to_string();
+/// Counts on the libuv thread pool instead of blocking the main thread.
+#[napi]
+pub fn count_module_graph_size_async_rs(
+  _env: Env,
+  entry_points: Vec<String>,
+  base_path: String,
+  ignore_module_extensions: Vec<String>,
+  builtin_modules: Vec<String>,
+  options: ResolverOptions,
+  graph_options: Option<ModuleGraphOptions>,
+) -> AsyncTask<CountModuleGraphTask> {
+  AsyncTask::new(CountModuleGraphTask::new(
+    entry_points,
+    base_path,
+    ignore_module_extensions,
+    builtin_modules,
+    options,
+    graph_options.unwrap_or_default(),
+  ))
+}
 
-      if builtin_modules.contains(&importee.replace("node:", "")) {
-        continue;
-      }
+/// Like `count_module_graph_size_rs`, but also returns the modules reached.
+#[napi]
+pub fn count_module_graph_modules_rs(
+  _env: Env,
+  entry_points: Vec<String>,
+  base_path: String,
+  ignore_module_extensions: Vec<String>,
+  builtin_modules: Vec<String>,
+  options: ResolverOptions,
+  graph_options: Option<ModuleGraphOptions>,
+) -> Result<ModuleGraphSize> {
+  let resolver = create_resolver(options)?;
+  walk_module_graph(
+    &entry_points,
+    &base_path,
+    &ignore_module_extensions,
+    &builtin_modules,
+    &resolver,
+    &graph_options.unwrap_or_default(),
+  )
+  .map(ModuleGraphSize::from)
+}
 
-      let importer = PathBuf::from(&base_path).join(&dep);
-      let parent_path = match importer.parent().unwrap().to_str() {
-        Some(path) => path,
-        None => {
-          return Err(Error::new(
-            GenericFailure,
-            format!("Failed to get parent path of: \"{}\"", &importer.display()),
-          ));
-        }
-      };
-      let resolved_url = match resolver.resolve(parent_path, &importee) {
-        Ok(url) => url,
-        Err(resolve_error) => {
-          return Err(Error::new(
-            GenericFailure,
-            format!(
-              "Failed to resolve importer: \"{}\", importee: \"{}\", parent_path: \"{}\", message: \"{}\", import.ss: \"{:?}\", import.se: \"{:?}\"",
-              &importer.display(),
-              &importee,
-              &parent_path,
-              &resolve_error,
-              &import.ss,
-              &import.se
-            ),
-          ));
-        }
-      };
+/// Like `count_module_graph_modules_rs`, but for each entry point on its own as well as for
+/// all of them together.
+#[napi]
+pub fn count_module_graph_per_entry_rs(
+  _env: Env,
+  entry_points: Vec<String>,
+  base_path: String,
+  ignore_module_extensions: Vec<String>,
+  builtin_modules: Vec<String>,
+  options: ResolverOptions,
+  graph_options: Option<ModuleGraphOptions>,
+) -> Result<ModuleGraphBreakdown> {
+  let resolver = create_resolver(options)?;
+  count_module_graph_per_entry(
+    &entry_points,
+    &base_path,
+    &ignore_module_extensions,
+    &builtin_modules,
+    &resolver,
+    &graph_options.unwrap_or_default(),
+  )
+}
 
-      let path_to_dependency = diff_paths(resolved_url.path(), &base_path).unwrap();
-      let path_to_dependency_str = path_to_dependency.to_str().unwrap().to_string();
+/// Walks the same graph as `count_module_graph_size_rs`, returning the modules reached and
+/// the imports between them.
+#[napi]
+pub fn build_module_graph_rs(
+  _env: Env,
+  entry_points: Vec<String>,
+  base_path: String,
+  ignore_module_extensions: Vec<String>,
+  builtin_modules: Vec<String>,
+  options: ResolverOptions,
+  graph_options: Option<ModuleGraphOptions>,
+) -> Result<ModuleGraph> {
+  let resolver = create_resolver(options)?;
+  walk_module_graph(
+    &entry_points,
+    &base_path,
+    &ignore_module_extensions,
+    &builtin_modules,
+    &resolver,
+    &graph_options.unwrap_or_default(),
+  )
+}
 
-      if !visited_modules.contains(&path_to_dependency_str) {
-        modules.push(path_to_dependency.clone());
-      }
-    }
-  }
+/// Walks the same graph as `build_module_graph_rs`, returning it as Graphviz DOT text with
+/// the barrel files classified by `barrel_options` filled in.
+#[napi]
+#[allow(clippy::too_many_arguments)]
+pub fn export_module_graph_dot_rs(
+  _env: Env,
+  entry_points: Vec<String>,
+  base_path: String,
+  ignore_module_extensions: Vec<String>,
+  builtin_modules: Vec<String>,
+  options: ResolverOptions,
+  graph_options: Option<ModuleGraphOptions>,
+  barrel_options: BarrelFileOptions,
+) -> Result<String> {
+  let resolver = create_resolver(options)?;
+  let graph = walk_module_graph(
+    &entry_points,
+    &base_path,
+    &ignore_module_extensions,
+    &builtin_modules,
+    &resolver,
+    &ModuleGraphOptions {
+      barrel_options: Some(barrel_options),
+      ..graph_options.unwrap_or_default()
+    },
+  )?;
+  Ok(module_graph_dot(&graph))
+}
 
-  Ok(visited_modules.len() as i32)
+/// Walks the same graph as `build_module_graph_rs`, returning it as JSON with the size and
+/// import counts of each module.
+#[napi]
+pub fn export_module_graph_json_rs(
+  _env: Env,
+  entry_points: Vec<String>,
+  base_path: String,
+  ignore_module_extensions: Vec<String>,
+  builtin_modules: Vec<String>,
+  options: ResolverOptions,
+  graph_options: Option<ModuleGraphOptions>,
+) -> Result<String> {
+  let resolver = create_resolver(options)?;
+  let graph = walk_module_graph(
+    &entry_points,
+    &base_path,
+    &ignore_module_extensions,
+    &builtin_modules,
+    &resolver,
+    &graph_options.unwrap_or_default(),
+  )?;
+  module_graph_json(&graph, &base_path)
 }
 
 #[cfg(test)]
@@ -265,5 +338,9 @@ mod tests {
     assert!(is_bare_module_specifier("bar"));
     assert!(!is_bare_module_specifier("/baz"));
     assert!(!is_bare_module_specifier("./qux"));
+    assert!(!is_bare_module_specifier("https://esm.sh/react"));
+    assert!(!is_bare_module_specifier(
+      "data:text/javascript,export default 1"
+    ));
   }
 }
@@ -0,0 +1,3481 @@
+//! Module graph traversal used by `count_module_graph_size_rs`,
+//! `count_module_graph_modules_rs`, `count_module_graph_per_entry_rs`, `build_module_graph_rs`,
+//! `export_module_graph_dot_rs` and `export_module_graph_json_rs`.
+//!
+//! Every list in a result is in the order modules were visited or imports were written, never
+//! in hash order, so results only differ between runs when the files or a `timeout_ms` do.
+
+use napi::Status::GenericFailure;
+use napi::bindgen_prelude::{FromNapiRef, FromNapiValue};
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, Error, Result, Status, Task, sys};
+use napi_derive::napi;
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{Argument, CallExpression, Program, Statement};
+use oxc_ast::{Visit, visit::walk};
+use oxc_parser::{ParseOptions, Parser};
+use oxc_span::{SourceType, Span};
+use pathdiff::diff_paths;
+use rayon::{Scope, ThreadPool, ThreadPoolBuilder};
+use regex::RegexSet;
+use serde::Serialize;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Condvar, LazyLock, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::barrel::{
+  BarrelFileOptions, ParseDiagnostic, classify_program, source_parse_diagnostics,
+};
+use crate::glob::GlobSet;
+use crate::lexer::{ImportType, ModuleLexer};
+use crate::resolver::{ModuleResolver, Resolved, ResolverOptions, create_resolver};
+use crate::sfc::{is_component_file, script_source};
+use crate::watch::{ChangeCallback, ModuleGraphWatcher};
+
+#[napi(object, object_to_js = false)]
+#[derive(Clone, Default)]
+pub struct ModuleGraphOptions {
+  /// Follow imports at most this many hops away from the entry points, which are 0 hops
+  /// away, unlimited by default
+  pub max_depth: Option<u32>,
+  /// Stop once this many modules are reached, leaving out the modules past it, for callers
+  /// that only need to know whether a graph exceeds a budget
+  pub stop_at: Option<u32>,
+  /// Stop once this many modules are reached, like `stop_at`, as a safeguard against graphs
+  /// too large to walk in an editor
+  pub max_modules: Option<u32>,
+  /// Stop visiting modules once the traversal has taken this long, as a safeguard against
+  /// graphs too slow to walk in an editor
+  pub timeout_ms: Option<u32>,
+  /// Threads to parse and resolve modules on, defaults to the available parallelism. Results
+  /// are the same whatever the concurrency, and walks with the same concurrency share their
+  /// threads
+  pub concurrency: Option<u32>,
+  /// Called every `progress_interval` visited modules. Calls are queued to the main thread, so
+  /// the synchronous functions only make them once they return
+  pub on_progress: Option<ProgressCallback>,
+  /// A `ModuleGraphCancellation` to stop the traversal with, which then fails with a
+  /// `Cancelled` error
+  pub cancellation: Option<CancellationFlag>,
+  /// How many modules to visit between `on_progress` calls, defaults to 100
+  pub progress_interval: Option<u32>,
+  /// Glob patterns, relative to `base_path`, of modules to leave out of the graph along with
+  /// the imports only they lead to, e.g. `**/*.test.ts` or `**/__mocks__/**`
+  pub exclude: Option<Vec<String>>,
+  /// Glob patterns or directories, relative to `base_path`, of the modules to parse. Modules
+  /// outside of them, such as generated code in `dist/`, are counted without following their
+  /// imports. Every module is parsed by default
+  pub include: Option<Vec<String>>,
+  /// Whether to follow `import()` expressions, which bundlers usually split into chunks that
+  /// don't load with the importer, defaults to `true`
+  pub follow_dynamic_imports: Option<bool>,
+  /// Whether to follow `require('./a')` and `require.resolve('./a')` calls with a string
+  /// literal, for the CommonJS modules of packages, defaults to `false`. Optional requires
+  /// guarded by a `try` that fail to resolve are reported like any unresolved import
+  pub follow_require_calls: Option<bool>,
+  /// Whether to look for import cycles among the static imports, for `cycles`
+  pub detect_cycles: Option<bool>,
+  /// Whether to count each package under `node_modules` as a single module named after the
+  /// package, e.g. `react` or `@scope/name`, without following its imports
+  pub collapse_packages: Option<bool>,
+  /// Whether to also split the first-party modules reached, those outside of `node_modules`,
+  /// by the workspace package they belong to, for `workspace_packages`, defaults to `false`
+  pub split_workspace_packages: Option<bool>,
+  /// What to do with imports that fail to resolve, defaults to `error`
+  pub on_unresolved: Option<UnresolvedImports>,
+  /// What to do with modules without a JavaScript, TypeScript or component extension, such as
+  /// `LICENSE` or bin shims, defaults to `leaf`
+  pub unknown_extensions: Option<UnknownExtensions>,
+  /// Regex patterns of specifiers to skip without resolving them, such as bundler virtual
+  /// modules or query suffixes, e.g. `^virtual:` or `\\?raw$`
+  pub ignore_specifiers: Option<Vec<String>>,
+  /// Whether to skip `import type` and other imports TypeScript erases, so the graph only
+  /// has the modules loaded at runtime, defaults to `true`
+  pub skip_type_only_imports: Option<bool>,
+  /// How much a module imported with a `type` import attribute, such as a JSON or CSS module,
+  /// adds to `size`, defaults to 1. Such modules are counted without being parsed
+  pub import_attribute_weight: Option<f64>,
+  /// Whether to count the modules reached through several symlinked paths once, by their real
+  /// path, as with `symlinks: false` in pnpm workspaces. Such modules keep the path they were
+  /// first reached through, defaults to `false`
+  pub dedupe_real_paths: Option<bool>,
+  /// List this many of the modules imported by the most other modules in `most_imported`,
+  /// none by default
+  pub most_imported: Option<u32>,
+  /// Classify each module parsed while walking with these options, for `barrels`
+  pub barrel_options: Option<BarrelFileOptions>,
+  /// Count modules larger than this many bytes without reading or following their imports,
+  /// such as generated bundles, unlimited by default
+  pub max_file_size: Option<u32>,
+  /// The extensions of stylesheets, images, fonts and other assets, which are counted
+  /// without being parsed, e.g. `css` or `.svg`, defaults to the common stylesheet, image,
+  /// font and media extensions
+  pub asset_extensions: Option<Vec<String>>,
+  /// How assets are counted, defaults to `count`
+  pub assets: Option<AssetImports>,
+  /// How `.json` modules are counted, which are never parsed, defaults to `count`
+  pub json_modules: Option<AssetImports>,
+  /// What the modules matching each pattern add to `size`, the first matching pattern
+  /// winning over the other weights. Modules matching none weigh as usual
+  pub module_weights: Option<Vec<ModuleWeight>>,
+}
+
+/// What the modules matching a glob pattern add to `size`, e.g. 0 for `**/*.d.ts`.
+#[napi(object, object_to_js = false)]
+#[derive(Clone)]
+pub struct ModuleWeight {
+  /// Glob pattern relative to `base_path`, e.g. `**/*.d.ts` or `src/generated/**`
+  pub pattern: String,
+  /// What each module adds to `size`, or with `per_byte` each byte of its source
+  pub weight: f64,
+  /// Whether `weight` is per byte of source, so `size` approximates bundle cost, defaults
+  /// to `false`
+  pub per_byte: Option<bool>,
+}
+
+/// The `asset_extensions` that are used when none are given.
+const DEFAULT_ASSET_EXTENSIONS: &[&str] = &[
+  "css", "scss", "sass", "less", "styl", "svg", "png", "jpg", "jpeg", "gif", "webp", "avif", "ico",
+  "bmp", "woff", "woff2", "ttf", "otf", "eot", "mp3", "mp4", "webm", "wav", "ogg",
+];
+
+#[napi(string_enum)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum AssetImports {
+  /// Count them as modules without parsing them, each adding 1 to `size`
+  #[napi(value = "count")]
+  Count,
+  /// Count them as modules without parsing them, each adding its size in bytes to `size`
+  #[napi(value = "bytes")]
+  Bytes,
+  /// Leave them out of the graph
+  #[napi(value = "skip")]
+  Skip,
+}
+
+#[napi(string_enum)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum UnknownExtensions {
+  /// Count them without following their imports
+  #[napi(value = "leaf")]
+  Leaf,
+  /// Parse them as ES modules
+  #[napi(value = "parse")]
+  Parse,
+}
+
+/// The budget that stopped a traversal early.
+#[napi(string_enum)]
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModuleGraphBudget {
+  /// More modules than `stop_at` or `max_modules` were left to visit
+  #[napi(value = "modules")]
+  Modules,
+  /// The traversal took longer than `timeout_ms`
+  #[napi(value = "time")]
+  Time,
+}
+
+/// A module that was counted but couldn't be walked as usual.
+#[napi(object)]
+#[derive(Clone, Serialize)]
+pub struct ModuleGraphWarning {
+  /// The module, relative to `base_path`
+  pub module: String,
+  pub message: String,
+}
+
+#[napi(string_enum)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum UnresolvedImports {
+  /// Fail the whole walk
+  #[napi(value = "error")]
+  Error,
+  /// Leave them out of the graph
+  #[napi(value = "ignore")]
+  Ignore,
+  /// Leave them out of the graph and list them in `unresolved`
+  #[napi(value = "collect")]
+  Collect,
+}
+
+/// A module that failed to parse, which was counted without following its imports.
+#[napi(object)]
+#[derive(Clone, Serialize)]
+pub struct ModuleGraphDiagnostic {
+  /// The module, relative to `base_path`
+  pub module: String,
+  /// The syntax errors oxc reported
+  pub errors: Vec<ParseDiagnostic>,
+}
+
+/// An import that failed to resolve.
+#[napi(object)]
+#[derive(Clone, Serialize)]
+pub struct UnresolvedImport {
+  /// The importing module, relative to `base_path`, or nothing for an entry point
+  pub importer: Option<String>,
+  /// The specifier as written in the import, e.g. `./button`
+  pub specifier: String,
+  /// Why the specifier failed to resolve
+  pub message: String,
+}
+
+/// `on_progress` behind a trait object, so that only converting it from JS needs the N-API
+/// threadsafe function symbols, which the test binary can't link.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(ModuleGraphProgress) + Send + Sync>);
+
+impl FromNapiValue for ProgressCallback {
+  unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+    let on_progress = unsafe {
+      ThreadsafeFunction::<ModuleGraphProgress, ErrorStrategy::Fatal>::from_napi_value(
+        env, napi_val,
+      )?
+    };
+    Ok(Self(Arc::new(move |progress| {
+      on_progress.call(progress, ThreadsafeFunctionCallMode::NonBlocking);
+    })))
+  }
+}
+
+/// Stops the module graph traversals it is passed to as `cancellation`, e.g. once ESLint moves
+/// on to the next file. Traversals check it before each module they visit or parse.
+#[napi]
+#[derive(Default)]
+pub struct ModuleGraphCancellation {
+  cancelled: Arc<AtomicBool>,
+}
+
+#[napi]
+impl ModuleGraphCancellation {
+  #[napi(constructor)]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  #[napi]
+  pub fn cancel(&self) {
+    self.cancelled.store(true, Ordering::Relaxed);
+  }
+
+  #[napi(getter)]
+  pub fn cancelled(&self) -> bool {
+    self.cancelled.load(Ordering::Relaxed)
+  }
+}
+
+/// The flag of a `ModuleGraphCancellation`, shared with the threads walking the graph.
+#[derive(Clone, Default)]
+pub struct CancellationFlag(Arc<AtomicBool>);
+
+impl FromNapiValue for CancellationFlag {
+  unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+    let cancellation = unsafe { ModuleGraphCancellation::from_napi_ref(env, napi_val)? };
+    Ok(Self(cancellation.cancelled.clone()))
+  }
+}
+
+impl CancellationFlag {
+  fn check(&self) -> Result<()> {
+    if self.0.load(Ordering::Relaxed) {
+      return Err(Error::new(
+        Status::Cancelled,
+        "Module graph traversal was cancelled".to_string(),
+      ));
+    }
+    Ok(())
+  }
+}
+
+#[napi(object)]
+pub struct ModuleGraphProgress {
+  /// Unique modules visited so far
+  pub count: u32,
+  /// The module just visited, relative to `base_path`
+  pub file: String,
+}
+
+#[napi(object)]
+pub struct ModuleGraphSize {
+  /// Unique modules reached
+  pub count: u32,
+  /// The modules reached, relative to `base_path`, in the order they were visited
+  pub modules: Vec<String>,
+  /// The size of the modules reached, in bytes of source
+  pub total_bytes: i64,
+  /// The modules reached, each weighing 1 unless weighted otherwise
+  pub size: f64,
+  /// The most imports between an entry point and a module reached, along the shortest chain
+  /// of imports to each module
+  pub depth: u32,
+  /// The distinct `node_modules` packages the modules reached belong to, by the name in
+  /// their nearest `package.json`, in the order they were first reached
+  pub packages: Vec<String>,
+  /// The modules reached outside of `node_modules`
+  pub first_party: ModuleGraphPartition,
+  /// The modules reached inside of `node_modules`
+  pub third_party: ModuleGraphPartition,
+  /// With `split_workspace_packages`, the first-party modules reached by the workspace
+  /// package they belong to, in the order the packages were first reached
+  pub workspace_packages: Vec<WorkspacePackageSize>,
+  /// With `most_imported`, the modules imported by the most other modules, most imported
+  /// first
+  pub most_imported: Vec<MostImportedModule>,
+  /// With `barrel_options`, the modules reached that are barrel files, in the order they were
+  /// visited. Modules counted without being parsed are never barrels
+  pub barrels: Vec<String>,
+  /// With `on_unresolved: "collect"`, the imports that failed to resolve
+  pub unresolved: Vec<UnresolvedImport>,
+  /// The modules that couldn't be walked as usual
+  pub warnings: Vec<ModuleGraphWarning>,
+  /// The modules that failed to parse, which were counted without following their imports
+  pub diagnostics: Vec<ModuleGraphDiagnostic>,
+  /// Whether the traversal stopped early, with modules left to visit after reaching
+  /// `stop_at` or `max_modules` modules or taking longer than `timeout_ms`
+  pub exceeded: bool,
+  /// The budget that stopped the traversal early, the modules reached until then being
+  /// counted as usual
+  pub exceeded_budget: Option<ModuleGraphBudget>,
+}
+
+/// The modules reached from each entry point on its own and from all of them together.
+#[napi(object)]
+pub struct ModuleGraphBreakdown {
+  /// The modules reached from each entry point, in the order of the entry points, each
+  /// listing its modules in the order of `union.modules`. Budgets such as `stop_at` apply to
+  /// `union`, and the entries of a `union` stopped early are `exceeded` as well
+  pub entries: Vec<ModuleGraphSize>,
+  /// The modules reached from any of the entry points
+  pub union: ModuleGraphSize,
+  /// The modules reached from each entry point and none of the others, in the order of the
+  /// entry points
+  pub unique: Vec<Vec<String>>,
+  /// The modules reached from more than one of the entry points, in the order of
+  /// `union.modules`
+  pub shared: Vec<String>,
+}
+
+/// The modules reached from a set of entry points and the imports between them.
+#[napi(object)]
+#[derive(Clone)]
+pub struct ModuleGraph {
+  /// The modules reached, relative to `base_path`, in the order they were visited
+  pub nodes: Vec<String>,
+  /// The static imports between `nodes`, in the order they were found
+  pub edges: Vec<ModuleGraphEdge>,
+  /// The `import()` expressions of `nodes`, in the order they were found. With
+  /// `follow_dynamic_imports: false` their importees are left out of `nodes`
+  pub dynamic_edges: Vec<ModuleGraphEdge>,
+  /// The size of `nodes`, in bytes of source
+  pub total_bytes: i64,
+  /// `nodes`, each weighing 1 unless weighted otherwise
+  pub size: f64,
+  /// The most imports between an entry point and any of `nodes`, along the shortest chain of
+  /// imports to each node
+  pub depth: u32,
+  /// The distinct `node_modules` packages `nodes` belong to, by the name in their nearest
+  /// `package.json`, in the order they were first reached
+  pub packages: Vec<String>,
+  /// The `nodes` outside of `node_modules`
+  pub first_party: ModuleGraphPartition,
+  /// The `nodes` inside of `node_modules`
+  pub third_party: ModuleGraphPartition,
+  /// With `split_workspace_packages`, the `nodes` outside of `node_modules` by the workspace
+  /// package they belong to, in the order the packages were first reached
+  pub workspace_packages: Vec<WorkspacePackageSize>,
+  /// With `most_imported`, the `nodes` imported by the most other nodes, most imported first
+  pub most_imported: Vec<MostImportedModule>,
+  /// With `barrel_options`, the `nodes` that are barrel files, in the order they were visited.
+  /// Nodes counted without being parsed are never barrels
+  pub barrels: Vec<String>,
+  /// With `on_unresolved: "collect"`, the imports that failed to resolve
+  pub unresolved: Vec<UnresolvedImport>,
+  /// The modules that couldn't be walked as usual
+  pub warnings: Vec<ModuleGraphWarning>,
+  /// The modules that failed to parse, which were counted without following their imports
+  pub diagnostics: Vec<ModuleGraphDiagnostic>,
+  /// With `detect_cycles`, the import cycles between `nodes`. Each cycle lists the modules in
+  /// import order, the last importing the first
+  pub cycles: Vec<Vec<String>>,
+  /// Whether the traversal stopped early, with modules left to visit after reaching
+  /// `stop_at` or `max_modules` modules or taking longer than `timeout_ms`
+  pub exceeded: bool,
+  /// The budget that stopped the traversal early, the modules reached until then being
+  /// counted as usual
+  pub exceeded_budget: Option<ModuleGraphBudget>,
+}
+
+#[napi(object)]
+#[derive(Clone, Serialize)]
+pub struct ModuleGraphEdge {
+  /// The importing module, relative to `base_path`
+  pub importer: String,
+  /// The imported module, relative to `base_path`
+  pub importee: String,
+  /// The specifier as written in the import, e.g. `./button`
+  pub specifier: String,
+}
+
+/// The modules reached on one side of a split, such as those inside of `node_modules`.
+#[napi(object)]
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleGraphPartition {
+  pub count: u32,
+  /// The size of the modules, in bytes of source
+  pub total_bytes: i64,
+  /// The modules, each weighing 1 unless weighted otherwise
+  pub size: f64,
+}
+
+impl ModuleGraphPartition {
+  fn add(&mut self, count: u32, bytes: u64, size: f64) {
+    self.count += count;
+    self.total_bytes += bytes as i64;
+    self.size += size;
+  }
+}
+
+/// The first-party modules reached that belong to one workspace package.
+#[napi(object)]
+#[derive(Clone, Serialize)]
+pub struct WorkspacePackageSize {
+  /// The name in the nearest `package.json` of the modules, those without a named
+  /// `package.json` being left out of `workspace_packages`
+  pub name: String,
+  pub modules: ModuleGraphPartition,
+}
+
+/// `first_party`, `third_party` and `workspace_packages` while the walk adds to them.
+#[derive(Default)]
+struct PartyTally {
+  first_party: ModuleGraphPartition,
+  third_party: ModuleGraphPartition,
+  workspace_packages: Vec<WorkspacePackageSize>,
+  /// The index in `workspace_packages` of the package of each first-party module
+  module_packages: HashMap<PathBuf, usize>,
+}
+
+impl PartyTally {
+  fn set_workspace_package(&mut self, dep: &Path, name: String) {
+    let index = match self
+      .workspace_packages
+      .iter()
+      .position(|package| package.name == name)
+    {
+      Some(index) => index,
+      None => {
+        self.workspace_packages.push(WorkspacePackageSize {
+          name,
+          modules: ModuleGraphPartition::default(),
+        });
+        self.workspace_packages.len() - 1
+      }
+    };
+    self.module_packages.insert(dep.to_path_buf(), index);
+  }
+
+  /// Adds a module reached, or with a `count` of 0 the bytes of one added before.
+  fn add(&mut self, dep: &Path, count: u32, bytes: u64, size: f64) {
+    match package_name(dep) {
+      Some(_) => self.third_party.add(count, bytes, size),
+      None => self.first_party.add(count, bytes, size),
+    }
+    if let Some(&index) = self.module_packages.get(dep) {
+      self.workspace_packages[index]
+        .modules
+        .add(count, bytes, size);
+    }
+  }
+}
+
+/// A module and how many other modules import it, statically or with `import()`.
+#[napi(object)]
+#[derive(Clone, Serialize)]
+pub struct MostImportedModule {
+  /// The module, relative to `base_path`
+  pub module: String,
+  pub importers: u32,
+}
+
+impl From<ModuleGraph> for ModuleGraphSize {
+  fn from(graph: ModuleGraph) -> Self {
+    Self {
+      count: graph.nodes.len() as u32,
+      modules: graph.nodes,
+      total_bytes: graph.total_bytes,
+      size: graph.size,
+      depth: graph.depth,
+      packages: graph.packages,
+      first_party: graph.first_party,
+      third_party: graph.third_party,
+      workspace_packages: graph.workspace_packages,
+      most_imported: graph.most_imported,
+      barrels: graph.barrels,
+      unresolved: graph.unresolved,
+      warnings: graph.warnings,
+      diagnostics: graph.diagnostics,
+      exceeded: graph.exceeded,
+      exceeded_budget: graph.exceeded_budget,
+    }
+  }
+}
+
+/// Walks the static imports of `entry_points` and returns the number of unique modules reached.
+pub fn count_module_graph_size(
+  entry_points: &[String],
+  base_path: &str,
+  ignore_module_extensions: &[String],
+  builtin_modules: &[String],
+  resolver: &ModuleResolver,
+  options: &ModuleGraphOptions,
+) -> Result<i32> {
+  walk_module_graph(
+    entry_points,
+    base_path,
+    ignore_module_extensions,
+    builtin_modules,
+    resolver,
+    options,
+  )
+  .map(|graph| graph.nodes.len() as i32)
+}
+
+/// Walks the static imports of `entry_points` once, and breaks the graph down by the entry
+/// points each module is reached from. Budgets such as `stop_at` apply to the graph of all of
+/// them, the graph of each entry point being as `exceeded` as it is.
+pub fn count_module_graph_per_entry(
+  entry_points: &[String],
+  base_path: &str,
+  ignore_module_extensions: &[String],
+  builtin_modules: &[String],
+  resolver: &ModuleResolver,
+  options: &ModuleGraphOptions,
+) -> Result<ModuleGraphBreakdown> {
+  let (graph, visited) = walk_visited(
+    entry_points,
+    base_path,
+    ignore_module_extensions,
+    builtin_modules,
+    resolver,
+    options,
+    None,
+  )?;
+  let indices: HashMap<&str, usize> = graph
+    .nodes
+    .iter()
+    .enumerate()
+    .map(|(index, node)| (node.as_str(), index))
+    .collect();
+  let mut imports = vec![Vec::new(); graph.nodes.len()];
+  let dynamic_edges = graph
+    .dynamic_edges
+    .iter()
+    .filter(|_| options.follow_dynamic_imports.unwrap_or(true));
+  for edge in graph.edges.iter().chain(dynamic_edges) {
+    if let (Some(&importer), Some(&importee)) = (
+      indices.get(edge.importer.as_str()),
+      indices.get(edge.importee.as_str()),
+    ) {
+      imports[importer].push(importee);
+    }
+  }
+
+  let entries: Vec<_> = entry_points
+    .iter()
+    .zip(&visited.entry_nodes)
+    .map(|(entry_point, entry_node)| {
+      // Breadth first from the entry point alone, for `max_depth` and `depth`
+      let mut depths = vec![None; graph.nodes.len()];
+      let mut queue = VecDeque::new();
+      if let Some(&index) = entry_node
+        .as_ref()
+        .and_then(|node| indices.get(node.as_str()))
+      {
+        depths[index] = Some(0);
+        queue.push_back(index);
+      }
+      while let Some(index) = queue.pop_front() {
+        let depth = depths[index].unwrap();
+        if options
+          .max_depth
+          .is_some_and(|max_depth| depth >= max_depth)
+        {
+          continue;
+        }
+        for &importee in &imports[index] {
+          if depths[importee].is_none() {
+            depths[importee] = Some(depth + 1);
+            queue.push_back(importee);
+          }
+        }
+      }
+      let reached: Vec<bool> = depths.iter().map(Option::is_some).collect();
+      let depth = depths.into_iter().flatten().max().unwrap_or(0);
+      let entry = graph_reached_from(
+        &graph,
+        &visited,
+        &indices,
+        &reached,
+        depth,
+        entry_point,
+        options,
+      );
+      (entry, reached)
+    })
+    .collect();
+
+  let mut reached_from = vec![0; graph.nodes.len()];
+  for (_, reached) in &entries {
+    for (count, reached) in reached_from.iter_mut().zip(reached) {
+      *count += u32::from(*reached);
+    }
+  }
+  let modules_reached_from = |reached: &[bool], shared: bool| {
+    graph
+      .nodes
+      .iter()
+      .zip(reached)
+      .zip(&reached_from)
+      .filter(|((_, reached), count)| **reached && (**count > 1) == shared)
+      .map(|((node, _), _)| node.clone())
+      .collect::<Vec<_>>()
+  };
+  Ok(ModuleGraphBreakdown {
+    unique: entries
+      .iter()
+      .map(|(_, reached)| modules_reached_from(reached, false))
+      .collect(),
+    shared: modules_reached_from(&vec![true; graph.nodes.len()], true),
+    entries: entries.into_iter().map(|(entry, _)| entry).collect(),
+    union: ModuleGraphSize::from(graph),
+  })
+}
+
+/// The part of `graph` that is `reached` from `entry_point`, in the order of `graph.nodes`.
+fn graph_reached_from(
+  graph: &ModuleGraph,
+  visited: &VisitedModules,
+  indices: &HashMap<&str, usize>,
+  reached: &[bool],
+  depth: u32,
+  entry_point: &str,
+  options: &ModuleGraphOptions,
+) -> ModuleGraphSize {
+  let is_reached = |module: &str| indices.get(module).is_some_and(|&index| reached[index]);
+  let mut modules = Vec::new();
+  let mut total_bytes = 0;
+  let mut size = 0.0;
+  let mut packages: Vec<String> = Vec::new();
+  let mut parties = PartyTally::default();
+  for ((node, module), _) in graph
+    .nodes
+    .iter()
+    .zip(&visited.modules)
+    .zip(reached)
+    .filter(|(_, reached)| **reached)
+  {
+    modules.push(node.clone());
+    total_bytes += module.bytes;
+    size += module.weight;
+    if let Some(package) = &module.package
+      && !packages.contains(package)
+    {
+      packages.push(package.clone());
+    }
+    if let Some(name) = &module.workspace_package {
+      parties.set_workspace_package(&module.dep, name.clone());
+    }
+    parties.add(&module.dep, 1, module.bytes, module.weight);
+  }
+  let most_imported = match options.most_imported {
+    Some(limit) => most_imported(
+      &modules,
+      graph
+        .edges
+        .iter()
+        .chain(&graph.dynamic_edges)
+        .filter(|edge| is_reached(&edge.importer)),
+      limit,
+    ),
+    None => Vec::new(),
+  };
+  ModuleGraphSize {
+    count: modules.len() as u32,
+    modules,
+    total_bytes: total_bytes as i64,
+    size,
+    depth,
+    packages,
+    first_party: parties.first_party,
+    third_party: parties.third_party,
+    workspace_packages: parties.workspace_packages,
+    most_imported,
+    barrels: graph
+      .barrels
+      .iter()
+      .filter(|barrel| is_reached(barrel))
+      .cloned()
+      .collect(),
+    unresolved: graph
+      .unresolved
+      .iter()
+      .filter(|import| match &import.importer {
+        Some(importer) => is_reached(importer),
+        None => import.specifier == entry_point,
+      })
+      .cloned()
+      .collect(),
+    warnings: graph
+      .warnings
+      .iter()
+      .filter(|warning| is_reached(&warning.module))
+      .cloned()
+      .collect(),
+    diagnostics: graph
+      .diagnostics
+      .iter()
+      .filter(|diagnostic| is_reached(&diagnostic.module))
+      .cloned()
+      .collect(),
+    exceeded: graph.exceeded,
+    exceeded_budget: graph.exceeded_budget,
+  }
+}
+
+/// Graphviz DOT text of `graph`, with its `barrels` filled in. Dynamic imports are dashed, and
+/// the edges of cycles red.
+pub fn module_graph_dot(graph: &ModuleGraph) -> String {
+  let barrels: HashSet<&String> = graph.barrels.iter().collect();
+  let cycle_edges: HashSet<(&str, &str)> = graph
+    .cycles
+    .iter()
+    .flat_map(|cycle| cycle.iter().zip(cycle.iter().cycle().skip(1)))
+    .map(|(importer, importee)| (importer.as_str(), importee.as_str()))
+    .collect();
+  let mut dot = String::from("digraph module_graph {\n  node [shape=box];\n");
+  for node in &graph.nodes {
+    dot.push_str(&format!("  {}", dot_id(node)));
+    if barrels.contains(node) {
+      dot.push_str(" [style=filled, fillcolor=orange]");
+    }
+    dot.push_str(";\n");
+  }
+  let edges = graph.edges.iter().map(|edge| (edge, false));
+  let dynamic_edges = graph.dynamic_edges.iter().map(|edge| (edge, true));
+  for (edge, dynamic) in edges.chain(dynamic_edges) {
+    let mut attributes = Vec::new();
+    if dynamic {
+      attributes.push("style=dashed");
+    }
+    if cycle_edges.contains(&(edge.importer.as_str(), edge.importee.as_str())) {
+      attributes.push("color=red");
+    }
+    dot.push_str(&format!(
+      "  {} -> {}",
+      dot_id(&edge.importer),
+      dot_id(&edge.importee)
+    ));
+    if !attributes.is_empty() {
+      dot.push_str(&format!(" [{}]", attributes.join(", ")));
+    }
+    dot.push_str(";\n");
+  }
+  dot.push_str("}\n");
+  dot
+}
+
+/// `graph` as JSON for tooling outside of Node, with the same camelCase keys as the N-API
+/// objects. Each node also lists its size on disk, unless it is not a file under `base_path`,
+/// and how many static imports it has and is the importee of.
+pub fn module_graph_json(graph: &ModuleGraph, base_path: &str) -> Result<String> {
+  let mut imports: HashMap<&str, u32> = HashMap::new();
+  let mut imported_by: HashMap<&str, u32> = HashMap::new();
+  for edge in &graph.edges {
+    *imports.entry(&edge.importer).or_default() += 1;
+    *imported_by.entry(&edge.importee).or_default() += 1;
+  }
+  let json = JsonModuleGraph {
+    nodes: graph
+      .nodes
+      .iter()
+      .map(|node| JsonModule {
+        id: node,
+        bytes: std::fs::metadata(Path::new(base_path).join(node))
+          .ok()
+          .filter(|metadata| metadata.is_file())
+          .map(|metadata| metadata.len()),
+        imports: imports.get(node.as_str()).copied().unwrap_or(0),
+        imported_by: imported_by.get(node.as_str()).copied().unwrap_or(0),
+      })
+      .collect(),
+    edges: &graph.edges,
+    dynamic_edges: &graph.dynamic_edges,
+    total_bytes: graph.total_bytes,
+    size: graph.size,
+    depth: graph.depth,
+    packages: &graph.packages,
+    first_party: &graph.first_party,
+    third_party: &graph.third_party,
+    workspace_packages: &graph.workspace_packages,
+    most_imported: &graph.most_imported,
+    barrels: &graph.barrels,
+    cycles: &graph.cycles,
+    unresolved: &graph.unresolved,
+    warnings: &graph.warnings,
+    diagnostics: &graph.diagnostics,
+    exceeded: graph.exceeded,
+    exceeded_budget: &graph.exceeded_budget,
+  };
+  serde_json::to_string(&json).map_err(|error| {
+    Error::new(
+      GenericFailure,
+      format!("Failed to serialize module graph, message: {}", error),
+    )
+  })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonModuleGraph<'a> {
+  nodes: Vec<JsonModule<'a>>,
+  edges: &'a [ModuleGraphEdge],
+  dynamic_edges: &'a [ModuleGraphEdge],
+  total_bytes: i64,
+  size: f64,
+  depth: u32,
+  packages: &'a [String],
+  first_party: &'a ModuleGraphPartition,
+  third_party: &'a ModuleGraphPartition,
+  workspace_packages: &'a [WorkspacePackageSize],
+  most_imported: &'a [MostImportedModule],
+  barrels: &'a [String],
+  cycles: &'a [Vec<String>],
+  unresolved: &'a [UnresolvedImport],
+  warnings: &'a [ModuleGraphWarning],
+  diagnostics: &'a [ModuleGraphDiagnostic],
+  exceeded: bool,
+  exceeded_budget: &'a Option<ModuleGraphBudget>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonModule<'a> {
+  id: &'a str,
+  bytes: Option<u64>,
+  imports: u32,
+  imported_by: u32,
+}
+
+fn dot_id(id: &str) -> String {
+  format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Walks module graphs with the options it was constructed with, keeping the imports of each
+/// module it parsed so the modules shared by many graphs are only parsed again once they
+/// change on disk.
+#[napi]
+pub struct ModuleGraphAnalyzer {
+  state: Arc<AnalyzerState>,
+}
+
+/// What a `ModuleGraphAnalyzer` walks with and caches, shared with the threads of its
+/// watchers.
+pub struct AnalyzerState {
+  ignore_module_extensions: Vec<String>,
+  builtin_modules: Vec<String>,
+  resolver: ModuleResolver,
+  options: ModuleGraphOptions,
+  cache: ModuleCache,
+  /// The graphs counted by `recount`, by `base_path` and entry points, until `invalidate`
+  /// drops the ones with a changed module
+  graphs: Mutex<HashMap<(String, Vec<String>), ModuleGraph>>,
+}
+
+impl AnalyzerState {
+  fn build(&self, entry_points: &[String], base_path: &str) -> Result<ModuleGraph> {
+    walk(
+      entry_points,
+      base_path,
+      &self.ignore_module_extensions,
+      &self.builtin_modules,
+      &self.resolver,
+      &self.options,
+      Some(&self.cache),
+    )
+  }
+
+  pub fn recount(&self, entry_points: &[String], base_path: &str) -> Result<ModuleGraph> {
+    let key = (base_path.to_string(), entry_points.to_vec());
+    if let Some(graph) = self.graphs.lock().unwrap().get(&key) {
+      return Ok(graph.clone());
+    }
+    let graph = self.build(entry_points, base_path)?;
+    self.graphs.lock().unwrap().insert(key, graph.clone());
+    Ok(graph)
+  }
+
+  /// Forgets what the resolver found on disk, for files created or deleted since.
+  pub fn clear_resolutions(&self) {
+    self.resolver.clear_cache();
+  }
+
+  pub fn invalidate(&self, path: &str) -> bool {
+    let is_path = |base_path: &str, module: &Path| {
+      Path::new(base_path).join(module) == Path::new(base_path).join(path)
+    };
+    self
+      .cache
+      .modules
+      .lock()
+      .unwrap()
+      .retain(|(base_path, dep), _| !is_path(base_path, dep));
+    let mut graphs = self.graphs.lock().unwrap();
+    let count = graphs.len();
+    graphs.retain(|(base_path, _), graph| {
+      !graph
+        .nodes
+        .iter()
+        .any(|node| is_path(base_path, Path::new(node)))
+    });
+    graphs.len() < count
+  }
+}
+
+#[napi]
+impl ModuleGraphAnalyzer {
+  #[napi(constructor)]
+  pub fn new(
+    ignore_module_extensions: Vec<String>,
+    builtin_modules: Vec<String>,
+    options: ResolverOptions,
+    graph_options: Option<ModuleGraphOptions>,
+  ) -> Result<Self> {
+    Ok(Self {
+      state: Arc::new(AnalyzerState {
+        ignore_module_extensions,
+        builtin_modules,
+        resolver: create_resolver(options)?,
+        options: graph_options.unwrap_or_default(),
+        cache: ModuleCache::default(),
+        graphs: Mutex::default(),
+      }),
+    })
+  }
+
+  /// Like `count_module_graph_size_rs`.
+  #[napi]
+  pub fn count(&self, entry_points: Vec<String>, base_path: String) -> Result<i32> {
+    self
+      .build(entry_points, base_path)
+      .map(|graph| graph.nodes.len() as i32)
+  }
+
+  /// Like `count_module_graph_modules_rs`.
+  #[napi]
+  pub fn count_modules(
+    &self,
+    entry_points: Vec<String>,
+    base_path: String,
+  ) -> Result<ModuleGraphSize> {
+    self
+      .build(entry_points, base_path)
+      .map(ModuleGraphSize::from)
+  }
+
+  /// Like `build_module_graph_rs`.
+  #[napi]
+  pub fn build(&self, entry_points: Vec<String>, base_path: String) -> Result<ModuleGraph> {
+    self.state.build(&entry_points, &base_path)
+  }
+
+  /// Like `count_modules`, but answered from the graph counted for the same entry points
+  /// before until one of its modules is passed to `invalidate`. Only the invalidated modules
+  /// and those changed on disk are parsed again when the graph is walked anew
+  #[napi]
+  pub fn recount(&self, entry_points: Vec<String>, base_path: String) -> Result<ModuleGraphSize> {
+    self
+      .state
+      .recount(&entry_points, &base_path)
+      .map(ModuleGraphSize::from)
+  }
+
+  /// Forgets the module at `path`, absolute or relative to `base_path`, after it changed, so
+  /// `recount` walks the graphs reaching it again. Creating or deleting a module can change
+  /// what the imports of other modules resolve to, which then have to be invalidated as well.
+  /// Returns whether a graph counted by `recount` reached the module
+  #[napi]
+  pub fn invalidate(&self, path: String) -> bool {
+    self.state.invalidate(&path)
+  }
+
+  /// Watches the modules of the graph of `entry_points` and the directories they are in,
+  /// walking it again with `recount` once no changes came for `debounce_ms`, 50 by default,
+  /// and calling `on_change` when that changes its `count` or `size`. The graph is counted
+  /// before returning, so errors walking it are thrown here. The watcher doesn't keep the
+  /// process alive
+  #[napi]
+  pub fn watch(
+    &self,
+    entry_points: Vec<String>,
+    base_path: String,
+    on_change: ChangeCallback,
+    debounce_ms: Option<u32>,
+  ) -> Result<ModuleGraphWatcher> {
+    ModuleGraphWatcher::start(
+      Arc::clone(&self.state),
+      entry_points,
+      base_path,
+      on_change,
+      Duration::from_millis(debounce_ms.unwrap_or(50).into()),
+    )
+  }
+
+  #[napi]
+  pub fn cache_stats(&self) -> ModuleCacheStats {
+    let cache = &self.state.cache;
+    ModuleCacheStats {
+      hits: cache.hits.load(Ordering::Relaxed),
+      misses: cache.misses.load(Ordering::Relaxed),
+      entries: cache.modules.lock().unwrap().len() as u32,
+    }
+  }
+
+  #[napi]
+  pub fn clear_cache(&self) {
+    let cache = &self.state.cache;
+    cache.modules.lock().unwrap().clear();
+    self.state.graphs.lock().unwrap().clear();
+    cache.hits.store(0, Ordering::Relaxed);
+    cache.misses.store(0, Ordering::Relaxed);
+  }
+}
+
+#[napi(object)]
+pub struct ModuleCacheStats {
+  /// Modules whose imports were answered from the cache
+  pub hits: u32,
+  /// Modules that had to be read and parsed
+  pub misses: u32,
+  /// Distinct modules currently cached
+  pub entries: u32,
+}
+
+#[derive(Default)]
+struct ModuleCache {
+  /// Parsed modules by `base_path` and their path relative to it, with their modification time
+  /// when they were parsed
+  modules: Mutex<HashMap<(String, PathBuf), (SystemTime, ParsedModule)>>,
+  hits: AtomicU32,
+  misses: AtomicU32,
+}
+
+impl ModuleCache {
+  fn module_imports(&self, dep: &Path, walk: &Walk, buffers: &mut Buffers) -> Result<ParsedModule> {
+    let base_path = walk.base_path;
+    let key = (base_path.to_string(), dep.to_path_buf());
+    let modified = std::fs::metadata(PathBuf::from(base_path).join(dep))
+      .and_then(|metadata| metadata.modified())
+      .ok();
+    if let Some(modified) = modified
+      && let Some((parsed_at, module)) = self.modules.lock().unwrap().get(&key)
+      && *parsed_at == modified
+    {
+      self.hits.fetch_add(1, Ordering::Relaxed);
+      return Ok(module.clone());
+    }
+
+    self.misses.fetch_add(1, Ordering::Relaxed);
+    let module = module_imports(dep, walk, buffers)?;
+    if let Some(modified) = modified {
+      self
+        .modules
+        .lock()
+        .unwrap()
+        .insert(key, (modified, module.clone()));
+    }
+    Ok(module)
+  }
+}
+
+/// Counts a module graph on the libuv thread pool.
+pub struct CountModuleGraphTask {
+  entry_points: Vec<String>,
+  base_path: String,
+  ignore_module_extensions: Vec<String>,
+  builtin_modules: Vec<String>,
+  /// Turned into a resolver when the task runs, off the main thread
+  resolver_options: ResolverOptions,
+  options: ModuleGraphOptions,
+}
+
+impl CountModuleGraphTask {
+  pub fn new(
+    entry_points: Vec<String>,
+    base_path: String,
+    ignore_module_extensions: Vec<String>,
+    builtin_modules: Vec<String>,
+    resolver_options: ResolverOptions,
+    options: ModuleGraphOptions,
+  ) -> Self {
+    Self {
+      entry_points,
+      base_path,
+      ignore_module_extensions,
+      builtin_modules,
+      resolver_options,
+      options,
+    }
+  }
+}
+
+#[napi]
+impl Task for CountModuleGraphTask {
+  type Output = i32;
+  type JsValue = i32;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    count_module_graph_size(
+      &self.entry_points,
+      &self.base_path,
+      &self.ignore_module_extensions,
+      &self.builtin_modules,
+      &create_resolver(self.resolver_options.clone())?,
+      &self.options,
+    )
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+/// Walks the static imports of `entry_points` and returns the unique modules reached and the
+/// imports between them.
+pub fn walk_module_graph(
+  entry_points: &[String],
+  base_path: &str,
+  ignore_module_extensions: &[String],
+  builtin_modules: &[String],
+  resolver: &ModuleResolver,
+  options: &ModuleGraphOptions,
+) -> Result<ModuleGraph> {
+  walk(
+    entry_points,
+    base_path,
+    ignore_module_extensions,
+    builtin_modules,
+    resolver,
+    options,
+    None,
+  )
+}
+
+/// Walks a module graph and reuses the parsed modules of `cache` that are unchanged on disk.
+fn walk(
+  entry_points: &[String],
+  base_path: &str,
+  ignore_module_extensions: &[String],
+  builtin_modules: &[String],
+  resolver: &ModuleResolver,
+  options: &ModuleGraphOptions,
+  cache: Option<&ModuleCache>,
+) -> Result<ModuleGraph> {
+  walk_visited(
+    entry_points,
+    base_path,
+    ignore_module_extensions,
+    builtin_modules,
+    resolver,
+    options,
+    cache,
+  )
+  .map(|(graph, _)| graph)
+}
+
+/// What a walk tallied for each module it visited, to break its graph down by entry point.
+struct VisitedModules {
+  /// The node each entry point was visited as, `None` for those left out of the graph
+  entry_nodes: Vec<Option<String>>,
+  /// In the order of the graph's `nodes`
+  modules: Vec<VisitedModule>,
+}
+
+#[derive(Default)]
+struct VisitedModule {
+  dep: PathBuf,
+  bytes: u64,
+  weight: f64,
+  /// The `node_modules` package the module belongs to
+  package: Option<String>,
+  /// With `split_workspace_packages`, the workspace package the module belongs to
+  workspace_package: Option<String>,
+}
+
+/// Like `walk`, also returning what it tallied for each module.
+fn walk_visited(
+  entry_points: &[String],
+  base_path: &str,
+  ignore_module_extensions: &[String],
+  builtin_modules: &[String],
+  resolver: &ModuleResolver,
+  options: &ModuleGraphOptions,
+  cache: Option<&ModuleCache>,
+) -> Result<(ModuleGraph, VisitedModules)> {
+  let mut visited_modules = HashSet::new();
+  let mut visited = Vec::new();
+  let mut entry_nodes = Vec::new();
+  let mut visit_order = Vec::new();
+  let mut edges = Vec::new();
+  let mut dynamic_edges = Vec::new();
+  let follow_dynamic_imports = options.follow_dynamic_imports.unwrap_or(true);
+  let mut exceeded_budget = None;
+  let max_modules = match (options.stop_at, options.max_modules) {
+    (Some(stop_at), Some(max_modules)) => Some(stop_at.min(max_modules)),
+    (stop_at, max_modules) => stop_at.or(max_modules),
+  };
+  let deadline = options
+    .timeout_ms
+    .map(|timeout_ms| Instant::now() + Duration::from_millis(timeout_ms.into()));
+  let mut total_bytes = 0;
+  let mut size = 0.0;
+  let mut unresolved = Vec::new();
+  let on_unresolved = options.on_unresolved.unwrap_or(UnresolvedImports::Error);
+  let mut warnings = Vec::new();
+  let mut diagnostics = Vec::new();
+  // Modules counted without parsing, which are only read for their size
+  let mut leaves = Vec::new();
+  let concurrency = match options.concurrency {
+    Some(concurrency) => concurrency.max(1) as usize,
+    None => std::thread::available_parallelism().map_or(1, usize::from),
+  };
+  let progress_interval = options.progress_interval.unwrap_or(100).max(1) as usize;
+  let exclude = GlobSet::new(options.exclude.as_deref().unwrap_or_default(), "exclude")?;
+  let module_weights = options.module_weights.as_deref().unwrap_or_default();
+  let weight_patterns = GlobSet::new(
+    &module_weights
+      .iter()
+      .map(|module_weight| module_weight.pattern.clone())
+      .collect::<Vec<_>>(),
+    "module_weights",
+  )?;
+  let include = match &options.include {
+    Some(include) => {
+      // A directory includes everything inside of it
+      let patterns: Vec<String> = include
+        .iter()
+        .flat_map(|pattern| {
+          [
+            pattern.clone(),
+            format!("{}/**", pattern.trim_end_matches('/')),
+          ]
+        })
+        .collect();
+      Some(GlobSet::new(&patterns, "include")?)
+    }
+    None => None,
+  };
+
+  let package = |dep: &Path| {
+    if options.collapse_packages.unwrap_or(false) {
+      package_name(dep)
+    } else {
+      None
+    }
+  };
+  let node_name = |dep: &Path| package(dep).unwrap_or_else(|| dep.to_str().unwrap().to_string());
+
+  // Breadth first, one depth at a time, so each module is first visited at its shortest
+  // distance for `max_depth`, while `Prefetch` parses the modules ahead in parallel
+  let mut level = Vec::new();
+  for file_path in entry_points {
+    let resolved_url = match resolver.resolve(base_path, file_path) {
+      Ok(Resolved::Module(url)) => url,
+      Ok(Resolved::Builtin(_) | Resolved::Url(_)) => {
+        entry_nodes.push(None);
+        continue;
+      }
+      Err(resolve_error) if on_unresolved != UnresolvedImports::Error => {
+        if on_unresolved == UnresolvedImports::Collect {
+          unresolved.push(UnresolvedImport {
+            importer: None,
+            specifier: file_path.clone(),
+            message: resolve_error.to_string(),
+          });
+        }
+        entry_nodes.push(None);
+        continue;
+      }
+      // A panic here would abort the process when counting on the libuv thread pool
+      Err(resolve_error) => {
+        return Err(Error::new(
+          GenericFailure,
+          format!(
+            "Failed to resolve entry point: \"{}\", base_path: \"{}\", message: \"{}\"",
+            file_path, base_path, resolve_error
+          ),
+        ));
+      }
+    };
+    let dep = diff_paths(resolved_url.full_path(), base_path).unwrap();
+    entry_nodes.push(Some(node_name(&dep)));
+    level.push((dep, false));
+  }
+
+  let context = Walk {
+    base_path,
+    builtin_modules,
+    resolver,
+    options,
+    ignore_specifiers: RegexSet::new(options.ignore_specifiers.as_deref().unwrap_or_default())
+      .map_err(|error| {
+        Error::new(
+          GenericFailure,
+          format!("Invalid ignore_specifiers pattern, message: {}", error),
+        )
+      })?,
+  };
+
+  let asset_extensions: HashSet<&str> = match &options.asset_extensions {
+    Some(extensions) => extensions
+      .iter()
+      .map(|extension| extension.trim_start_matches('.'))
+      .collect(),
+    None => DEFAULT_ASSET_EXTENSIONS.iter().copied().collect(),
+  };
+  let assets = options.assets.unwrap_or(AssetImports::Count);
+  let json_modules = options.json_modules.unwrap_or(AssetImports::Count);
+  let mut depth = 0;
+  let mut depth_reached = 0;
+  let mut barrels = Vec::new();
+  if let Some(barrel_options) = &options.barrel_options {
+    barrel_options.barrel_filename_regex()?;
+  }
+  let mut packages = Vec::new();
+  let mut seen_packages = HashSet::new();
+  let split_workspace_packages = options.split_workspace_packages.unwrap_or(false);
+  let mut parties = PartyTally::default();
+  let dedupe_real_paths = options.dedupe_real_paths.unwrap_or(false);
+  // The node each real path was first reached as, and the other nodes that were its aliases
+  let mut real_paths: HashMap<PathBuf, String> = HashMap::new();
+  let mut aliases: HashMap<String, String> = HashMap::new();
+  let module_kind = |dep: &Path| {
+    let module_extension = dep
+      .extension()
+      .and_then(|extension| extension.to_str())
+      .unwrap_or_default();
+    let asset = if module_extension == "json" {
+      Some(json_modules)
+    } else if asset_extensions.contains(module_extension) {
+      Some(assets)
+    } else {
+      None
+    };
+    if ignore_module_extensions.contains(&module_extension.to_string())
+      || exclude.is_match(dep.to_str().unwrap())
+      || asset == Some(AssetImports::Skip)
+    {
+      ModuleKind::Skipped
+    } else if asset.is_some()
+      || package(dep).is_some()
+      || include
+        .as_ref()
+        .is_some_and(|include| !include.is_match(dep.to_str().unwrap()))
+    {
+      ModuleKind::Leaf(asset)
+    } else {
+      ModuleKind::Parsed
+    }
+  };
+  let below_max_depth = |depth| options.max_depth.is_none_or(|max_depth| depth < max_depth);
+  let parses = |dep: &Path, depth: u32, with_type: bool| {
+    !with_type && below_max_depth(depth) && matches!(module_kind(dep), ModuleKind::Parsed)
+  };
+  let prefetch = Prefetch {
+    walk: &context,
+    cache,
+    parses: &parses,
+    follow_dynamic_imports,
+    tracks_depth: options.max_depth.is_some(),
+    max_modules,
+    deadline,
+    cancellation: options.cancellation.as_ref(),
+    modules: Mutex::default(),
+    parsed: Condvar::new(),
+    buffers: Mutex::default(),
+    stopped: AtomicBool::new(false),
+  };
+  // The thread walking the graph parses modules too
+  let pool = match concurrency {
+    1 => None,
+    concurrency => Some(thread_pool(concurrency - 1)?),
+  };
+
+  with_scope(pool.as_deref(), |scope| -> Result<()> {
+    // Modules still queued once the graph is walked are left unparsed
+    let _stop = StopOnDrop(&prefetch.stopped);
+    while !level.is_empty() && exceeded_budget.is_none() {
+      let mut to_parse = Vec::new();
+      for (dep, with_type) in std::mem::take(&mut level) {
+        if let Some(cancellation) = &options.cancellation {
+          cancellation.check()?;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+          exceeded_budget = Some(ModuleGraphBudget::Time);
+          break;
+        }
+        let path = PathBuf::from(base_path).join(&dep);
+        let node = node_name(&dep);
+
+        let (asset, parsed) = match module_kind(&dep) {
+          ModuleKind::Skipped => continue,
+          ModuleKind::Leaf(asset) => (asset, false),
+          ModuleKind::Parsed => (None, true),
+        };
+        if dedupe_real_paths
+          && package(&dep).is_none()
+          && let Ok(real_path) = std::fs::canonicalize(&path)
+        {
+          match real_paths.entry(real_path) {
+            Entry::Occupied(entry) => {
+              if *entry.get() != node {
+                aliases.insert(node, entry.get().clone());
+              }
+              continue;
+            }
+            Entry::Vacant(entry) => {
+              entry.insert(node.clone());
+            }
+          }
+        }
+        // A module can be imported by several modules of the previous depth
+        if visited_modules.contains(&node) {
+          continue;
+        }
+        // Checked before the module is visited, so a graph stops at exactly `max_modules`
+        if max_modules.is_some_and(|max_modules| visit_order.len() >= max_modules as usize) {
+          exceeded_budget = Some(ModuleGraphBudget::Modules);
+          break;
+        }
+        visited_modules.insert(node.clone());
+        visit_order.push(node.clone());
+        depth_reached = depth;
+        let mut module = VisitedModule {
+          dep: dep.clone(),
+          ..VisitedModule::default()
+        };
+        if let Some(directory_name) = package_name(&dep) {
+          // The directory name stands in for packages without a name
+          let name = resolver
+            .package_for_path(path.to_str().unwrap())
+            .and_then(|package| package.name)
+            .unwrap_or(directory_name);
+          if seen_packages.insert(name.clone()) {
+            packages.push(name.clone());
+          }
+          module.package = Some(name);
+        } else if split_workspace_packages
+          && let Some(name) = resolver
+            .package_for_path(path.to_str().unwrap())
+            .and_then(|package| package.name)
+        {
+          parties.set_workspace_package(&dep, name.clone());
+          module.workspace_package = Some(name);
+        }
+        let file_size = || std::fs::metadata(&path).map_or(0.0, |metadata| metadata.len() as f64);
+        let weight = match (
+          weight_patterns
+            .first_match(dep.to_str().unwrap())
+            .map(|index| &module_weights[index]),
+          options.import_attribute_weight,
+        ) {
+          (Some(module_weight), _) if module_weight.per_byte.unwrap_or(false) => {
+            module_weight.weight * file_size()
+          }
+          (Some(module_weight), _) => module_weight.weight,
+          (None, Some(weight)) if with_type => weight,
+          _ if asset == Some(AssetImports::Bytes) => file_size(),
+          _ => 1.0,
+        };
+        size += weight;
+        parties.add(&dep, 1, 0, weight);
+        module.weight = weight;
+        visited.push(module);
+        if let Some(on_progress) = &options.on_progress
+          && visit_order.len() % progress_interval == 0
+        {
+          (on_progress.0)(ModuleGraphProgress {
+            count: visit_order.len() as u32,
+            file: node,
+          });
+        }
+        if !parsed || with_type || !below_max_depth(depth) {
+          leaves.push((dep, visited.len() - 1));
+          continue;
+        }
+        to_parse.push((dep, visited.len() - 1));
+      }
+
+      // Queued first so the threads parse them while they are taken in order
+      for (dep, _) in &to_parse {
+        prefetch.reach(scope, dep.clone(), depth, false);
+      }
+      for (dep, index) in &to_parse {
+        let module = prefetch.take(scope, dep)?;
+        total_bytes += module.bytes;
+        visited[*index].bytes = module.bytes;
+        parties.add(dep, 0, module.bytes, 0.0);
+        if module.is_barrel {
+          barrels.push(node_name(dep));
+        }
+        unresolved.extend(module.unresolved);
+        warnings.extend(module.warnings);
+        diagnostics.extend(module.diagnostic);
+        for import in module.imports {
+          let path_to_dependency_str = node_name(&import.path);
+          let follow = !import.dynamic || follow_dynamic_imports;
+
+          if follow && !visited_modules.contains(&path_to_dependency_str) {
+            level.push((import.path, import.with_type));
+          }
+          let edge = ModuleGraphEdge {
+            importer: dep.to_str().unwrap().to_string(),
+            importee: path_to_dependency_str,
+            specifier: import.specifier,
+          };
+          if import.dynamic {
+            dynamic_edges.push(edge);
+          } else {
+            edges.push(edge);
+          }
+        }
+      }
+      depth += 1;
+    }
+    Ok(())
+  })?;
+
+  for (leaf, index) in leaves {
+    let bytes =
+      std::fs::metadata(PathBuf::from(base_path).join(&leaf)).map_or(0, |metadata| metadata.len());
+    total_bytes += bytes;
+    visited[index].bytes = bytes;
+    parties.add(&leaf, 0, bytes, 0.0);
+  }
+
+  for edge in edges.iter_mut().chain(&mut dynamic_edges) {
+    if let Some(node) = aliases.get(&edge.importee) {
+      edge.importee = node.clone();
+    }
+  }
+  for entry_node in &mut entry_nodes {
+    if let Some(node) = entry_node.as_ref().and_then(|node| aliases.get(node)) {
+      *entry_node = Some(node.clone());
+    }
+    if entry_node
+      .as_ref()
+      .is_some_and(|node| !visited_modules.contains(node))
+    {
+      *entry_node = None;
+    }
+  }
+  // Leave out imports of modules that were never visited, such as ignored extensions
+  edges.retain(|edge| visited_modules.contains(&edge.importee));
+  if follow_dynamic_imports {
+    dynamic_edges.retain(|edge| visited_modules.contains(&edge.importee));
+  }
+  let cycles = if options.detect_cycles.unwrap_or(false) {
+    find_cycles(&visit_order, &edges)
+  } else {
+    Vec::new()
+  };
+  let most_imported = match options.most_imported {
+    Some(limit) => most_imported(&visit_order, edges.iter().chain(&dynamic_edges), limit),
+    None => Vec::new(),
+  };
+  let graph = ModuleGraph {
+    nodes: visit_order,
+    edges,
+    dynamic_edges,
+    total_bytes: total_bytes as i64,
+    size,
+    depth: depth_reached,
+    packages,
+    first_party: parties.first_party,
+    third_party: parties.third_party,
+    workspace_packages: parties.workspace_packages,
+    most_imported,
+    barrels,
+    cycles,
+    unresolved,
+    warnings,
+    diagnostics,
+    exceeded: exceeded_budget.is_some(),
+    exceeded_budget,
+  };
+  Ok((
+    graph,
+    VisitedModules {
+      entry_nodes,
+      modules: visited,
+    },
+  ))
+}
+
+/// The `limit` nodes with the most distinct importers, the nodes visited first going first
+/// among those with as many.
+fn most_imported<'a>(
+  nodes: &[String],
+  edges: impl Iterator<Item = &'a ModuleGraphEdge>,
+  limit: u32,
+) -> Vec<MostImportedModule> {
+  let mut importers: HashMap<&str, HashSet<&str>> = HashMap::new();
+  for edge in edges {
+    if edge.importer != edge.importee {
+      importers
+        .entry(&edge.importee)
+        .or_default()
+        .insert(&edge.importer);
+    }
+  }
+  let mut most_imported: Vec<_> = nodes
+    .iter()
+    .filter_map(|node| {
+      let importers = importers.get(node.as_str())?.len() as u32;
+      Some(MostImportedModule {
+        module: node.clone(),
+        importers,
+      })
+    })
+    .collect();
+  // Stable, so the order of visits breaks ties
+  most_imported.sort_by_key(|module| std::cmp::Reverse(module.importers));
+  most_imported.truncate(limit as usize);
+  most_imported
+}
+
+/// An import of a module, resolved relative to `base_path`.
+#[derive(Clone)]
+struct ModuleImport {
+  path: PathBuf,
+  /// The specifier as written in the import
+  specifier: String,
+  /// Whether the import is an `import()` expression
+  dynamic: bool,
+  /// Whether the import has a `type` import attribute, e.g. `with { type: 'json' }`
+  with_type: bool,
+}
+
+/// The name of the package `path` is inside of, from the directories after its last
+/// `node_modules` directory.
+fn package_name(path: &Path) -> Option<String> {
+  let components: Vec<_> = path
+    .iter()
+    .map(|component| component.to_string_lossy())
+    .collect();
+  let node_modules = components
+    .iter()
+    .rposition(|component| component == "node_modules")?;
+  let name = components.get(node_modules + 1)?;
+  if name.starts_with('@') {
+    Some(format!("{}/{}", name, components.get(node_modules + 2)?))
+  } else {
+    Some(name.to_string())
+  }
+}
+
+/// Finds a cycle for each import back to a module still being walked in a depth first walk of
+/// `edges`, which finds every module that is part of a cycle without listing every cycle.
+fn find_cycles(nodes: &[String], edges: &[ModuleGraphEdge]) -> Vec<Vec<String>> {
+  let mut imports: HashMap<&str, Vec<&str>> = HashMap::new();
+  for edge in edges {
+    let importees = imports.entry(edge.importer.as_str()).or_default();
+    if !importees.contains(&edge.importee.as_str()) {
+      importees.push(edge.importee.as_str());
+    }
+  }
+
+  let mut cycles = Vec::new();
+  // The position of each module on `path` while it is walked, `None` once it is done
+  let mut walked: HashMap<&str, Option<usize>> = HashMap::new();
+  for root in nodes {
+    if walked.contains_key(root.as_str()) {
+      continue;
+    }
+    let mut path = vec![root.as_str()];
+    let mut next_import = vec![0];
+    walked.insert(root, Some(0));
+    while let (Some(&module), Some(index)) = (path.last(), next_import.last_mut()) {
+      let Some(&importee) = imports
+        .get(module)
+        .and_then(|importees| importees.get(*index))
+      else {
+        walked.insert(module, None);
+        path.pop();
+        next_import.pop();
+        continue;
+      };
+      *index += 1;
+      match walked.get(importee) {
+        Some(Some(position)) => cycles.push(
+          path[*position..]
+            .iter()
+            .map(|module| module.to_string())
+            .collect(),
+        ),
+        Some(None) => {}
+        None => {
+          walked.insert(importee, Some(path.len()));
+          path.push(importee);
+          next_import.push(0);
+        }
+      }
+    }
+  }
+  cycles
+}
+
+/// What parsing the modules of a walk needs.
+struct Walk<'a> {
+  base_path: &'a str,
+  builtin_modules: &'a [String],
+  resolver: &'a ModuleResolver,
+  options: &'a ModuleGraphOptions,
+  ignore_specifiers: RegexSet,
+}
+
+/// A parsed module and its resolved imports.
+#[derive(Clone)]
+struct ParsedModule {
+  /// The size of the source, in bytes
+  bytes: u64,
+  imports: Vec<ModuleImport>,
+  unresolved: Vec<UnresolvedImport>,
+  warnings: Vec<ModuleGraphWarning>,
+  /// Whether the module is a barrel file by `barrel_options`
+  is_barrel: bool,
+  /// Why the module failed to parse, leaving it without imports
+  diagnostic: Option<ModuleGraphDiagnostic>,
+}
+
+/// The source and parser arena of a thread parsing modules, reused from one module to the
+/// next so a walk only allocates as much as its largest modules need.
+#[derive(Default)]
+struct Buffers {
+  source: String,
+  allocator: Allocator,
+}
+
+/// Parses `dep` and resolves its imports.
+fn module_imports(dep: &Path, walk: &Walk, buffers: &mut Buffers) -> Result<ParsedModule> {
+  let Walk {
+    base_path,
+    builtin_modules,
+    resolver,
+    options,
+    ignore_specifiers,
+  } = walk;
+  let path = PathBuf::from(base_path).join(dep);
+  let mut warnings = Vec::new();
+  let is_component = is_component_file(&path.to_string_lossy());
+  let unknown_extension = !is_component && SourceType::from_path(&path).is_err();
+  let leaf = |message: &str| {
+    Ok(ParsedModule {
+      bytes: std::fs::metadata(&path).map_or(0, |metadata| metadata.len()),
+      imports: Vec::new(),
+      unresolved: Vec::new(),
+      warnings: vec![ModuleGraphWarning {
+        module: dep.to_str().unwrap().to_string(),
+        message: message.to_string(),
+      }],
+      is_barrel: false,
+      diagnostic: None,
+    })
+  };
+  if unknown_extension && options.unknown_extensions != Some(UnknownExtensions::Parse) {
+    // Not read, it may well not be text
+    return leaf("Unknown extension, counted without following its imports");
+  }
+  if let Some(max_file_size) = options.max_file_size
+    && std::fs::metadata(&path).is_ok_and(|metadata| metadata.len() > max_file_size.into())
+  {
+    return leaf("Larger than max_file_size, counted without following its imports");
+  }
+
+  buffers.source.clear();
+  buffers.allocator.reset();
+  let Buffers { source, allocator } = buffers;
+  match File::open(&path).and_then(|mut file| file.read_to_string(source)) {
+    Ok(_) => {}
+    Err(error) if unknown_extension && error.kind() == ErrorKind::InvalidData => {
+      return leaf("Unknown extension and not UTF-8, counted without following its imports");
+    }
+    Err(_) => {
+      return Err(Error::new(
+        GenericFailure,
+        format!("Failed to read file: \"{}{}\"", base_path, &dep.display()),
+      ));
+    }
+  };
+  if unknown_extension {
+    warnings.push(ModuleGraphWarning {
+      module: dep.to_str().unwrap().to_string(),
+      message: "Unknown extension, parsed as an ES module".to_string(),
+    });
+  }
+
+  let bytes = source.len() as u64;
+  let file_source = &*source;
+  let script;
+  let (source, source_type) = if is_component {
+    script = script_source(source);
+    (&script.0, script.1)
+  } else {
+    let source_type = SourceType::from_path(&path).unwrap_or(SourceType::mjs());
+    (&*source, source_type)
+  };
+  let parse_options = ParseOptions {
+    allow_return_outside_function: source_type.is_script(),
+    ..ParseOptions::default()
+  };
+  let ret = Parser::new(allocator, source, source_type)
+    .with_options(parse_options)
+    .parse();
+  // Classified from the program parsed for the imports, the module isn't parsed twice
+  let is_barrel = options
+    .barrel_options
+    .as_ref()
+    .is_some_and(|barrel_options| {
+      let barrel_options = BarrelFileOptions {
+        file_path: Some(path.to_string_lossy().into_owned()),
+        ..barrel_options.clone()
+      };
+      classify_program(&ret, file_source, source_type, &barrel_options).is_barrel
+    });
+  if ret.panicked || !ret.errors.is_empty() {
+    return Ok(ParsedModule {
+      bytes,
+      imports: Vec::new(),
+      unresolved: Vec::new(),
+      warnings,
+      is_barrel,
+      diagnostic: Some(ModuleGraphDiagnostic {
+        module: dep.to_str().unwrap().to_string(),
+        errors: source_parse_diagnostics(source, &ret),
+      }),
+    });
+  }
+  let ModuleLexer { imports, .. } = ModuleLexer::new().build(&ret.program);
+  let mut specifiers: Vec<_> = imports
+    .into_iter()
+    .filter(|import| !(import.t && options.skip_type_only_imports.unwrap_or(true)))
+    .filter_map(|import| {
+      let dynamic = matches!(import.d, ImportType::DynamicImport(_));
+      Some((
+        import.n?.to_string(),
+        dynamic,
+        Span::new(import.ss, import.se),
+      ))
+    })
+    .collect();
+  let with_type = attribute_imports(&ret.program);
+  if options.follow_require_calls.unwrap_or(false) {
+    let mut require_calls = RequireCalls::default();
+    require_calls.visit_program(&ret.program);
+    specifiers.extend(
+      require_calls
+        .requires
+        .into_iter()
+        .map(|(specifier, span)| (specifier, false, span)),
+    );
+  }
+
+  let mut resolved = Vec::new();
+  let mut unresolved = Vec::new();
+  let on_unresolved = options.on_unresolved.unwrap_or(UnresolvedImports::Error);
+  for (importee, dynamic, span) in specifiers {
+    if builtin_modules.contains(&importee.replace("node:", ""))
+      || ignore_specifiers.is_match(&importee)
+    {
+      continue;
+    }
+
+    let parent_path = match path.parent().unwrap().to_str() {
+      Some(path) => path,
+      None => {
+        return Err(Error::new(
+          GenericFailure,
+          format!("Failed to get parent path of: \"{}\"", &path.display()),
+        ));
+      }
+    };
+    let resolved_url = match resolver.resolve(parent_path, &importee) {
+      Ok(Resolved::Module(url)) => url,
+      // Builtins and URLs are left to the runtime
+      Ok(Resolved::Builtin(_) | Resolved::Url(_)) => continue,
+      Err(resolve_error) if on_unresolved != UnresolvedImports::Error => {
+        if on_unresolved == UnresolvedImports::Collect {
+          unresolved.push(UnresolvedImport {
+            importer: Some(dep.to_str().unwrap().to_string()),
+            specifier: importee,
+            message: resolve_error.to_string(),
+          });
+        }
+        continue;
+      }
+      Err(resolve_error) => {
+        return Err(Error::new(
+          GenericFailure,
+          format!(
+            "Failed to resolve importer: \"{}\", importee: \"{}\", parent_path: \"{}\", message: \"{}\", import.ss: \"{:?}\", import.se: \"{:?}\"",
+            &path.display(),
+            &importee,
+            &parent_path,
+            &resolve_error,
+            &span.start,
+            &span.end
+          ),
+        ));
+      }
+    };
+
+    resolved.push(ModuleImport {
+      path: diff_paths(resolved_url.path(), base_path).unwrap(),
+      specifier: importee,
+      dynamic,
+      with_type: with_type.contains(&span.start),
+    });
+  }
+  Ok(ParsedModule {
+    bytes,
+    imports: resolved,
+    unresolved,
+    warnings,
+    is_barrel,
+    diagnostic: None,
+  })
+}
+
+/// The start of each import and re-export statement with a `type` import attribute.
+fn attribute_imports(program: &Program) -> HashSet<u32> {
+  program
+    .body
+    .iter()
+    .filter_map(|statement| {
+      let (span, with_clause) = match statement {
+        Statement::ImportDeclaration(import) => (import.span, &import.with_clause),
+        Statement::ExportNamedDeclaration(export) => (export.span, &export.with_clause),
+        Statement::ExportAllDeclaration(export) => (export.span, &export.with_clause),
+        _ => return None,
+      };
+      with_clause
+        .as_ref()?
+        .with_entries
+        .iter()
+        .any(|attribute| attribute.key.as_atom() == "type")
+        .then_some(span.start)
+    })
+    .collect()
+}
+
+/// Collects the specifiers of `require` and `require.resolve` calls with a string literal.
+#[derive(Default)]
+struct RequireCalls {
+  requires: Vec<(String, Span)>,
+}
+
+impl<'a> Visit<'a> for RequireCalls {
+  fn visit_call_expression(&mut self, call: &CallExpression<'a>) {
+    let specifier = call.common_js_require().or_else(|| {
+      if !call.callee.is_specific_member_access("require", "resolve") {
+        return None;
+      }
+      match call.arguments.first() {
+        Some(Argument::StringLiteral(specifier)) => Some(specifier),
+        _ => None,
+      }
+    });
+    if let Some(specifier) = specifier {
+      self.requires.push((specifier.value.to_string(), call.span));
+    }
+    walk::walk_call_expression(self, call);
+  }
+}
+
+/// How a walk treats a module, whatever imports it.
+enum ModuleKind {
+  /// Left out of the graph, e.g. for its extension
+  Skipped,
+  /// Counted without following its imports, as an asset if it is one
+  Leaf(Option<AssetImports>),
+  /// Parsed for its imports, unless `max_depth` or a `type` import attribute make it a leaf
+  Parsed,
+}
+
+/// Parses the modules of a walk ahead of it on a thread pool. Each module parsed queues the
+/// modules it imports for any thread to take, while the walk takes the modules it visits in
+/// order, parsing those no thread has started on itself.
+struct Prefetch<'a> {
+  walk: &'a Walk<'a>,
+  cache: Option<&'a ModuleCache>,
+  /// Whether a module reached at a depth is parsed, possibly with a `type` import attribute
+  parses: &'a (dyn Fn(&Path, u32, bool) -> bool + Sync),
+  follow_dynamic_imports: bool,
+  /// Whether to parse modules again reached at a shorter depth, for `max_depth`
+  tracks_depth: bool,
+  /// Beyond this many modules only those the walk takes are parsed
+  max_modules: Option<u32>,
+  deadline: Option<Instant>,
+  cancellation: Option<&'a CancellationFlag>,
+  modules: Mutex<HashMap<PathBuf, Prefetched>>,
+  /// Notified each time a thread finishes parsing a module
+  parsed: Condvar,
+  buffers: Mutex<Vec<Buffers>>,
+  stopped: AtomicBool,
+}
+
+struct Prefetched {
+  /// The shortest depth the module was reached at
+  depth: u32,
+  state: PrefetchState,
+  /// The imports of the module that are followed, once it is parsed
+  imports: Vec<(PathBuf, bool)>,
+}
+
+enum PrefetchState {
+  Queued,
+  Parsing,
+  Parsed(Result<ParsedModule>),
+  /// Taken by the walk
+  Taken,
+}
+
+impl<'a> Prefetch<'a> {
+  /// Queues `dep` to be parsed, or its imports again when it was parsed at a longer depth.
+  fn reach<'s>(&'s self, scope: Option<&Scope<'s>>, dep: PathBuf, depth: u32, with_type: bool) {
+    if self.stopped.load(Ordering::Relaxed) || !(self.parses)(&dep, depth, with_type) {
+      return;
+    }
+    let depth = if self.tracks_depth { depth } else { 0 };
+    let mut modules = self.modules.lock().unwrap();
+    let queued = modules.len();
+    let imports = match modules.get_mut(&dep) {
+      Some(prefetched) if depth < prefetched.depth => {
+        prefetched.depth = depth;
+        prefetched.imports.clone()
+      }
+      Some(_) => return,
+      None
+        if self
+          .max_modules
+          .is_some_and(|max_modules| queued > max_modules as usize) =>
+      {
+        return;
+      }
+      None => {
+        modules.insert(
+          dep.clone(),
+          Prefetched {
+            depth,
+            state: PrefetchState::Queued,
+            imports: Vec::new(),
+          },
+        );
+        drop(modules);
+        if let Some(scope) = scope {
+          scope.spawn(move |scope| self.parse_queued(scope, &dep));
+        }
+        return;
+      }
+    };
+    drop(modules);
+    self.reach_imports(scope, &imports, depth + 1);
+  }
+
+  fn reach_imports<'s>(
+    &'s self,
+    scope: Option<&Scope<'s>>,
+    imports: &[(PathBuf, bool)],
+    depth: u32,
+  ) {
+    for (dep, with_type) in imports {
+      self.reach(scope, dep.clone(), depth, *with_type);
+    }
+  }
+
+  /// Parses `dep` on a thread of the pool, unless the walk took it first.
+  fn parse_queued<'s>(&'s self, scope: &Scope<'s>, dep: &Path) {
+    if self.stopped.load(Ordering::Relaxed)
+      || self
+        .deadline
+        .is_some_and(|deadline| Instant::now() >= deadline)
+      || self
+        .cancellation
+        .is_some_and(|cancellation| cancellation.check().is_err())
+    {
+      return;
+    }
+    {
+      let mut modules = self.modules.lock().unwrap();
+      let prefetched = modules.get_mut(dep).unwrap();
+      if !matches!(prefetched.state, PrefetchState::Queued) {
+        return;
+      }
+      prefetched.state = PrefetchState::Parsing;
+    }
+    let (module, imports) = {
+      let _requeue = RequeueOnPanic {
+        prefetch: self,
+        dep,
+      };
+      let module = self.parse(dep);
+      let imports = self.followed_imports(&module);
+      (module, imports)
+    };
+    let depth = {
+      let mut modules = self.modules.lock().unwrap();
+      let prefetched = modules.get_mut(dep).unwrap();
+      prefetched.state = PrefetchState::Parsed(module);
+      prefetched.imports = imports.clone();
+      prefetched.depth
+    };
+    self.parsed.notify_all();
+    self.reach_imports(Some(scope), &imports, depth + 1);
+  }
+
+  /// The parsed `dep`, waiting for the thread parsing it or parsing it on this thread.
+  fn take<'s>(&'s self, scope: Option<&Scope<'s>>, dep: &Path) -> Result<ParsedModule> {
+    let mut modules = self.modules.lock().unwrap();
+    loop {
+      let prefetched = modules
+        .entry(dep.to_path_buf())
+        .or_insert_with(|| Prefetched {
+          depth: 0,
+          state: PrefetchState::Queued,
+          imports: Vec::new(),
+        });
+      match std::mem::replace(&mut prefetched.state, PrefetchState::Taken) {
+        PrefetchState::Parsed(module) => return module,
+        PrefetchState::Parsing => {
+          prefetched.state = PrefetchState::Parsing;
+          modules = self.parsed.wait(modules).unwrap();
+        }
+        PrefetchState::Queued | PrefetchState::Taken => {
+          drop(modules);
+          let module = self.parse(dep);
+          let imports = self.followed_imports(&module);
+          let depth = {
+            let mut modules = self.modules.lock().unwrap();
+            let prefetched = modules.get_mut(dep).unwrap();
+            prefetched.imports = imports.clone();
+            prefetched.depth
+          };
+          self.reach_imports(scope, &imports, depth + 1);
+          return module;
+        }
+      }
+    }
+  }
+
+  fn parse(&self, dep: &Path) -> Result<ParsedModule> {
+    let mut buffers = self.buffers.lock().unwrap().pop().unwrap_or_default();
+    let module = match self.cache {
+      Some(cache) => cache.module_imports(dep, self.walk, &mut buffers),
+      None => module_imports(dep, self.walk, &mut buffers),
+    };
+    self.buffers.lock().unwrap().push(buffers);
+    module
+  }
+
+  fn followed_imports(&self, module: &Result<ParsedModule>) -> Vec<(PathBuf, bool)> {
+    module.as_ref().map_or_else(
+      |_| Vec::new(),
+      |module| {
+        module
+          .imports
+          .iter()
+          .filter(|import| !import.dynamic || self.follow_dynamic_imports)
+          .map(|import| (import.path.clone(), import.with_type))
+          .collect()
+      },
+    )
+  }
+}
+
+/// Queues a module a thread of the pool is parsing again if the parse panics, so the walk
+/// waiting for it parses it itself instead of waiting forever.
+struct RequeueOnPanic<'p, 'a> {
+  prefetch: &'p Prefetch<'a>,
+  dep: &'p Path,
+}
+
+impl Drop for RequeueOnPanic<'_, '_> {
+  fn drop(&mut self) {
+    if !std::thread::panicking() {
+      return;
+    }
+    let mut modules = self
+      .prefetch
+      .modules
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner);
+    if let Some(prefetched) = modules.get_mut(self.dep)
+      && matches!(prefetched.state, PrefetchState::Parsing)
+    {
+      prefetched.state = PrefetchState::Queued;
+    }
+    drop(modules);
+    self.prefetch.parsed.notify_all();
+  }
+}
+
+/// Sets the flag once dropped, however the scope it is dropped in is left.
+struct StopOnDrop<'a>(&'a AtomicBool);
+
+impl Drop for StopOnDrop<'_> {
+  fn drop(&mut self) {
+    self.0.store(true, Ordering::Relaxed);
+  }
+}
+
+/// Runs `op` in a scope of `pool`, or without one to spawn on.
+fn with_scope<'scope, R>(
+  pool: Option<&ThreadPool>,
+  op: impl FnOnce(Option<&Scope<'scope>>) -> R,
+) -> R {
+  match pool {
+    Some(pool) => pool.in_place_scope(|scope| op(Some(scope))),
+    None => op(None),
+  }
+}
+
+/// The thread pool with `threads` threads, started by the first walk that needs it and kept
+/// for those after.
+fn thread_pool(threads: usize) -> Result<Arc<ThreadPool>> {
+  static THREAD_POOLS: LazyLock<Mutex<HashMap<usize, Arc<ThreadPool>>>> =
+    LazyLock::new(Mutex::default);
+  let mut pools = THREAD_POOLS.lock().unwrap();
+  if let Some(pool) = pools.get(&threads) {
+    return Ok(Arc::clone(pool));
+  }
+  let pool = ThreadPoolBuilder::new()
+    .num_threads(threads)
+    .thread_name(|index| format!("module-graph-{index}"))
+    .build()
+    .map_err(|error| {
+      Error::new(
+        GenericFailure,
+        format!(
+          "Failed to start the module graph threads, message: \"{}\"",
+          error
+        ),
+      )
+    })?;
+  let pool = Arc::new(pool);
+  pools.insert(threads, Arc::clone(&pool));
+  Ok(pool)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn fixture(name: &str) -> String {
+    format!("{}/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+  }
+
+  /// The size on disk of the given modules of a fixture
+  fn fixture_bytes(name: &str, modules: &[impl AsRef<str>]) -> i64 {
+    modules
+      .iter()
+      .map(|module| {
+        std::fs::metadata(format!("{}/{}", fixture(name), module.as_ref()))
+          .unwrap()
+          .len() as i64
+      })
+      .sum()
+  }
+
+  fn try_walk_fixture(
+    name: &str,
+    entry_points: &[&str],
+    resolver_options: ResolverOptions,
+    options: ModuleGraphOptions,
+  ) -> Result<ModuleGraph> {
+    walk_module_graph(
+      &entry_points
+        .iter()
+        .map(|entry_point| entry_point.to_string())
+        .collect::<Vec<_>>(),
+      &fixture(name),
+      &[],
+      &[],
+      &create_resolver(resolver_options)?,
+      &options,
+    )
+  }
+
+  /// Walks the `./index.js` of a fixture with the default resolver
+  fn walk_fixture(name: &str, options: ModuleGraphOptions) -> ModuleGraph {
+    try_walk_fixture(name, &["./index.js"], ResolverOptions::default(), options).unwrap()
+  }
+
+  #[test]
+  fn test_count_follows_package_imports() {
+    let resolver = create_resolver(ResolverOptions {
+      condition_names: Some(vec!["import".to_string()]),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let base_path = fixture("imports");
+
+    let count = count_module_graph_size(
+      &["./src/index.js".to_string()],
+      &base_path,
+      &[],
+      &[],
+      &resolver,
+      &ModuleGraphOptions::default(),
+    );
+    assert_eq!(count.unwrap(), 3);
+  }
+
+  #[test]
+  fn test_count_skips_url_imports() {
+    let resolver = create_resolver(ResolverOptions::default()).unwrap();
+    let base_path = fixture("url-imports");
+
+    let count = count_module_graph_size(
+      &["./index.js".to_string()],
+      &base_path,
+      &[],
+      &[],
+      &resolver,
+      &ModuleGraphOptions::default(),
+    );
+    assert_eq!(count.unwrap(), 2);
+  }
+
+  #[test]
+  fn test_count_parses_vue_components() {
+    let resolver = create_resolver(ResolverOptions::default()).unwrap();
+    let base_path = fixture("vue");
+
+    let count = count_module_graph_size(
+      &["./index.js".to_string()],
+      &base_path,
+      &[],
+      &[],
+      &resolver,
+      &ModuleGraphOptions::default(),
+    );
+    assert_eq!(count.unwrap(), 4);
+  }
+
+  #[test]
+  fn test_walk_lists_modules() {
+    let graph = walk_fixture("vue", ModuleGraphOptions::default());
+    assert_eq!(graph.nodes[0], "index.js");
+    let mut nodes = graph.nodes.clone();
+    nodes.sort();
+    assert_eq!(nodes, ["App.vue", "Button.vue", "index.js", "label.ts"]);
+
+    let edges: Vec<_> = graph
+      .edges
+      .iter()
+      .map(|edge| (&*edge.importer, &*edge.importee, &*edge.specifier))
+      .collect();
+    assert_eq!(
+      edges,
+      [
+        ("index.js", "App.vue", "./App.vue"),
+        ("App.vue", "Button.vue", "./Button.vue"),
+        ("App.vue", "label.ts", "./label.ts"),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_count_max_depth() {
+    let resolver = create_resolver(ResolverOptions::default()).unwrap();
+    let base_path = fixture("vue");
+    let count = |max_depth| {
+      count_module_graph_size(
+        &["./index.js".to_string()],
+        &base_path,
+        &[],
+        &[],
+        &resolver,
+        &ModuleGraphOptions {
+          max_depth: Some(max_depth),
+          ..ModuleGraphOptions::default()
+        },
+      )
+      .unwrap()
+    };
+
+    assert_eq!(count(0), 1);
+    assert_eq!(count(1), 2);
+    assert_eq!(count(2), 4);
+  }
+
+  #[test]
+  fn test_walk_stops_at_threshold() {
+    let walk = |stop_at| {
+      walk_fixture(
+        "vue",
+        ModuleGraphOptions {
+          stop_at: Some(stop_at),
+          ..ModuleGraphOptions::default()
+        },
+      )
+    };
+
+    let graph = walk(2);
+    assert!(graph.exceeded);
+    assert_eq!(graph.nodes.len(), 2);
+    assert!(
+      graph
+        .edges
+        .iter()
+        .all(|edge| graph.nodes.contains(&edge.importee))
+    );
+    assert_eq!(graph.exceeded_budget, Some(ModuleGraphBudget::Modules));
+    let graph = walk(3);
+    assert!(graph.exceeded);
+    assert_eq!(graph.nodes.len(), 3);
+    // Reaching the limit with nothing left to visit isn't exceeding it
+    let graph = walk(4);
+    assert!(!graph.exceeded);
+    assert_eq!(graph.exceeded_budget, None);
+    assert_eq!(graph.nodes.len(), 4);
+  }
+
+  #[test]
+  fn test_walk_budgets() {
+    let walk = |options| walk_fixture("vue", options);
+
+    let graph = walk(ModuleGraphOptions {
+      max_modules: Some(2),
+      stop_at: Some(3),
+      ..ModuleGraphOptions::default()
+    });
+    assert_eq!(graph.exceeded_budget, Some(ModuleGraphBudget::Modules));
+    assert_eq!(graph.nodes.len(), 2);
+
+    let graph = walk(ModuleGraphOptions {
+      max_modules: Some(4),
+      stop_at: Some(3),
+      ..ModuleGraphOptions::default()
+    });
+    assert_eq!(graph.exceeded_budget, Some(ModuleGraphBudget::Modules));
+    assert_eq!(graph.nodes.len(), 3);
+
+    let graph = walk(ModuleGraphOptions {
+      timeout_ms: Some(0),
+      ..ModuleGraphOptions::default()
+    });
+    assert!(graph.exceeded);
+    assert_eq!(graph.exceeded_budget, Some(ModuleGraphBudget::Time));
+    assert!(graph.nodes.is_empty());
+
+    let graph = walk(ModuleGraphOptions {
+      timeout_ms: Some(60_000),
+      ..ModuleGraphOptions::default()
+    });
+    assert_eq!(graph.exceeded_budget, None);
+    assert_eq!(graph.nodes.len(), 4);
+  }
+
+  #[test]
+  fn test_walk_is_deterministic_across_concurrency() {
+    let walk = |concurrency| {
+      walk_fixture(
+        "vue",
+        ModuleGraphOptions {
+          concurrency: Some(concurrency),
+          ..ModuleGraphOptions::default()
+        },
+      )
+    };
+
+    let serial = walk(1);
+    let parallel = walk(4);
+    assert_eq!(serial.nodes, parallel.nodes);
+    let specifiers = |graph: &ModuleGraph| {
+      graph
+        .edges
+        .iter()
+        .map(|edge| edge.specifier.clone())
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(specifiers(&serial), specifiers(&parallel));
+  }
+
+  #[test]
+  fn test_count_missing_entry_point_errors() {
+    let resolver = create_resolver(ResolverOptions::default()).unwrap();
+    let base_path = fixture("vue");
+
+    let error = count_module_graph_size(
+      &["./missing.js".to_string()],
+      &base_path,
+      &[],
+      &[],
+      &resolver,
+      &ModuleGraphOptions::default(),
+    )
+    .unwrap_err();
+    assert!(
+      error
+        .reason
+        .starts_with("Failed to resolve entry point: \"./missing.js\"")
+    );
+  }
+
+  #[test]
+  fn test_walk_reports_progress() {
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let reported = Arc::clone(&progress);
+
+    walk_fixture(
+      "vue",
+      ModuleGraphOptions {
+        on_progress: Some(ProgressCallback(Arc::new(move |progress| {
+          reported
+            .lock()
+            .unwrap()
+            .push((progress.count, progress.file));
+        }))),
+        progress_interval: Some(2),
+        ..ModuleGraphOptions::default()
+      },
+    );
+    assert_eq!(
+      *progress.lock().unwrap(),
+      vec![(2, "App.vue".to_string()), (4, "label.ts".to_string())]
+    );
+  }
+
+  #[test]
+  fn test_walk_excludes_globs() {
+    let walk = |exclude: &[&str]| {
+      try_walk_fixture(
+        "vue",
+        &["./index.js"],
+        ResolverOptions::default(),
+        ModuleGraphOptions {
+          exclude: Some(exclude.iter().map(|pattern| pattern.to_string()).collect()),
+          ..ModuleGraphOptions::default()
+        },
+      )
+    };
+
+    let graph = walk(&["**/Button.vue"]).unwrap();
+    assert_eq!(graph.nodes, vec!["index.js", "App.vue", "label.ts"]);
+    assert!(graph.edges.iter().all(|edge| edge.importee != "Button.vue"));
+    assert!(walk(&["src/[a-"]).is_err());
+  }
+
+  #[test]
+  fn test_walk_only_parses_included_modules() {
+    let walk = |include: &[&str]| {
+      try_walk_fixture(
+        "imports",
+        &["./src/index.js"],
+        ResolverOptions {
+          condition_names: Some(vec!["import".to_string()]),
+          ..ResolverOptions::default()
+        },
+        ModuleGraphOptions {
+          include: Some(include.iter().map(|pattern| pattern.to_string()).collect()),
+          ..ModuleGraphOptions::default()
+        },
+      )
+      .unwrap()
+      .nodes
+    };
+
+    assert_eq!(walk(&["src"]).len(), 3);
+    assert_eq!(walk(&["**/index.js"]).len(), 3);
+    // The entry point is still counted, but not parsed
+    assert_eq!(walk(&["src/utils/"]), vec!["src/index.js"]);
+  }
+
+  #[test]
+  fn test_walk_follows_reference_sources() {
+    let walk = |prefer_reference_source: bool| {
+      try_walk_fixture(
+        "tsconfig-references",
+        &["./packages/app/src/index.ts"],
+        ResolverOptions {
+          extensions: Some(vec![".ts".to_string(), ".js".to_string()]),
+          discover_tsconfig: Some(true),
+          prefer_reference_source: Some(prefer_reference_source),
+          ..ResolverOptions::default()
+        },
+        ModuleGraphOptions::default(),
+      )
+      .unwrap()
+      .nodes
+    };
+
+    assert_eq!(
+      walk(false),
+      vec![
+        "packages/app/src/index.ts",
+        "packages/shared/dist/index.js",
+        "packages/shared/dist/format.js"
+      ]
+    );
+    assert_eq!(
+      walk(true),
+      vec![
+        "packages/app/src/index.ts",
+        "packages/shared/src/index.ts",
+        "packages/shared/src/format.ts"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_walk_dynamic_imports() {
+    let walk = |follow_dynamic_imports| {
+      walk_fixture(
+        "dynamic-imports",
+        ModuleGraphOptions {
+          follow_dynamic_imports: Some(follow_dynamic_imports),
+          ..ModuleGraphOptions::default()
+        },
+      )
+    };
+
+    let graph = walk(true);
+    assert_eq!(graph.nodes, vec!["index.js", "static.js", "lazy.js"]);
+    assert_eq!(graph.edges.len(), 2);
+    assert_eq!(graph.dynamic_edges.len(), 1);
+
+    let graph = walk(false);
+    assert_eq!(graph.nodes, vec!["index.js", "static.js"]);
+    assert_eq!(graph.edges.len(), 1);
+    assert_eq!(graph.dynamic_edges[0].importee, "lazy.js");
+    assert_eq!(graph.dynamic_edges[0].specifier, "./lazy.js");
+  }
+
+  #[test]
+  fn test_walk_follows_require_calls() {
+    let walk = |follow_require_calls| {
+      walk_fixture(
+        "commonjs",
+        ModuleGraphOptions {
+          follow_require_calls: Some(follow_require_calls),
+          ..ModuleGraphOptions::default()
+        },
+      )
+    };
+
+    let graph = walk(true);
+    assert_eq!(graph.nodes, vec!["index.js", "lib.cjs", "a.cjs", "b.cjs"]);
+    assert_eq!(graph.edges[2].specifier, "./b.cjs");
+    assert_eq!(walk(false).nodes, vec!["index.js", "lib.cjs"]);
+    let graph = walk_fixture("commonjs", ModuleGraphOptions::default());
+    assert_eq!(graph.nodes, vec!["index.js", "lib.cjs"]);
+  }
+
+  #[test]
+  fn test_walk_totals_bytes() {
+    let walk = |max_depth| {
+      walk_fixture(
+        "commonjs",
+        ModuleGraphOptions {
+          max_depth,
+          ..ModuleGraphOptions::default()
+        },
+      )
+    };
+
+    let graph = walk(None);
+    assert_eq!(graph.total_bytes, fixture_bytes("commonjs", &graph.nodes));
+    // Leaves are read for their size too
+    let graph = walk(Some(0));
+    assert_eq!(graph.nodes, vec!["index.js"]);
+    assert_eq!(graph.total_bytes, fixture_bytes("commonjs", &["index.js"]));
+  }
+
+  #[test]
+  fn test_walk_detects_cycles() {
+    let walk = |detect_cycles| {
+      walk_fixture(
+        "cycles",
+        ModuleGraphOptions {
+          detect_cycles: Some(detect_cycles),
+          ..ModuleGraphOptions::default()
+        },
+      )
+      .cycles
+    };
+
+    assert_eq!(
+      walk(true),
+      vec![vec!["a.js", "b.js"], vec!["index.js", "a.js", "b.js"]]
+    );
+    assert!(walk(false).is_empty());
+  }
+
+  #[test]
+  fn test_package_name() {
+    assert_eq!(
+      package_name(Path::new("node_modules/react/index.js")).as_deref(),
+      Some("react")
+    );
+    assert_eq!(
+      package_name(Path::new(
+        "../node_modules/a/node_modules/@scope/b/lib/b.js"
+      ))
+      .as_deref(),
+      Some("@scope/b")
+    );
+    assert_eq!(package_name(Path::new("src/index.js")), None);
+  }
+
+  #[test]
+  fn test_walk_collapses_packages() {
+    let walk = |collapse_packages| {
+      try_walk_fixture(
+        "browser-field",
+        &["./src/index.js"],
+        ResolverOptions::default(),
+        ModuleGraphOptions {
+          collapse_packages: Some(collapse_packages),
+          ..ModuleGraphOptions::default()
+        },
+      )
+      .unwrap()
+    };
+
+    let graph = walk(true);
+    assert_eq!(graph.nodes, vec!["src/index.js", "isomorphic"]);
+    assert_eq!(graph.edges[0].importee, "isomorphic");
+    assert_eq!(
+      walk(false).nodes,
+      vec!["src/index.js", "node_modules/isomorphic/lib/node.js"]
+    );
+    assert_eq!(graph.packages, vec!["isomorphic"]);
+    assert_eq!(walk(false).packages, vec!["isomorphic"]);
+  }
+
+  #[test]
+  fn test_walk_splits_first_and_third_party() {
+    let walk = |symlinks: bool| {
+      try_walk_fixture(
+        "tsconfig-references",
+        &["./packages/app/src/index.ts"],
+        ResolverOptions {
+          extensions: Some(vec![".ts".to_string(), ".js".to_string()]),
+          symlinks: Some(symlinks),
+          ..ResolverOptions::default()
+        },
+        ModuleGraphOptions {
+          split_workspace_packages: Some(true),
+          ..ModuleGraphOptions::default()
+        },
+      )
+      .unwrap()
+    };
+
+    // Through the symlink the shared package is first-party, the way workspaces link it
+    let graph = walk(true);
+    assert_eq!(graph.first_party.count, 3);
+    assert_eq!(graph.first_party.total_bytes, graph.total_bytes);
+    assert_eq!(graph.third_party.count, 0);
+    let packages: Vec<_> = graph
+      .workspace_packages
+      .iter()
+      .map(|package| (package.name.as_str(), package.modules.count))
+      .collect();
+    assert_eq!(packages, vec![("@acme/app", 1), ("@acme/shared", 2)]);
+
+    let graph = walk(false);
+    assert_eq!(graph.first_party.count, 1);
+    assert_eq!(graph.third_party.count, 2);
+    assert_eq!(graph.third_party.size, 2.0);
+    assert_eq!(
+      graph.first_party.total_bytes + graph.third_party.total_bytes,
+      graph.total_bytes
+    );
+    assert_eq!(graph.workspace_packages.len(), 1);
+  }
+
+  #[test]
+  fn test_walk_module_weights() {
+    let walk = |module_weights: &[(&str, f64, bool)]| {
+      try_walk_fixture(
+        "tsconfig-references",
+        &["./packages/app/src/index.ts"],
+        ResolverOptions {
+          extensions: Some(vec![".ts".to_string(), ".js".to_string()]),
+          ..ResolverOptions::default()
+        },
+        ModuleGraphOptions {
+          module_weights: Some(
+            module_weights
+              .iter()
+              .map(|(pattern, weight, per_byte)| ModuleWeight {
+                pattern: pattern.to_string(),
+                weight: *weight,
+                per_byte: Some(*per_byte),
+              })
+              .collect(),
+          ),
+          ..ModuleGraphOptions::default()
+        },
+      )
+    };
+
+    assert_eq!(walk(&[]).unwrap().size, 3.0);
+    // The first matching pattern wins
+    let graph = walk(&[
+      ("packages/shared/dist/format.js", 0.0, false),
+      ("packages/shared/**", 0.25, false),
+      ("**/*.ts", 0.5, true),
+    ])
+    .unwrap();
+    let size =
+      0.5 * fixture_bytes("tsconfig-references", &["packages/app/src/index.ts"]) as f64 + 0.25;
+    assert_eq!(graph.size, size);
+    assert_eq!(graph.first_party.size, size);
+    assert!(walk(&[("[a-", 1.0, false)]).is_err());
+  }
+
+  #[test]
+  fn test_count_per_entry() {
+    let resolver = create_resolver(ResolverOptions::default()).unwrap();
+    let base_path = fixture("vue");
+
+    let breakdown = count_module_graph_per_entry(
+      &[
+        "./App.vue".to_string(),
+        "./label.ts".to_string(),
+        "./index.js".to_string(),
+      ],
+      &base_path,
+      &[],
+      &[],
+      &resolver,
+      &ModuleGraphOptions::default(),
+    )
+    .unwrap();
+    let counts: Vec<_> = breakdown.entries.iter().map(|entry| entry.count).collect();
+    assert_eq!(counts, vec![3, 1, 4]);
+    assert_eq!(breakdown.entries[1].modules, vec!["label.ts"]);
+    assert_eq!(breakdown.union.count, 4);
+    assert_eq!(
+      breakdown.unique,
+      vec![Vec::<String>::new(), vec![], vec!["index.js".to_string()]]
+    );
+    assert_eq!(breakdown.shared.len(), 3);
+    assert!(breakdown.shared.contains(&"label.ts".to_string()));
+
+    // Each entry point's graph is the one walking it on its own finds
+    for (entry_point, entry) in ["./App.vue", "./label.ts", "./index.js"]
+      .iter()
+      .zip(&breakdown.entries)
+    {
+      let graph = try_walk_fixture(
+        "vue",
+        &[entry_point],
+        ResolverOptions::default(),
+        ModuleGraphOptions::default(),
+      )
+      .unwrap();
+      assert_eq!(entry.total_bytes, graph.total_bytes);
+      assert_eq!(entry.depth, graph.depth);
+      let mut modules = entry.modules.clone();
+      let mut nodes = graph.nodes.clone();
+      modules.sort();
+      nodes.sort();
+      assert_eq!(modules, nodes);
+    }
+
+    // Budgets stop the walk of all of them, which the entries report
+    let breakdown = count_module_graph_per_entry(
+      &["./label.ts".to_string(), "./index.js".to_string()],
+      &base_path,
+      &[],
+      &[],
+      &resolver,
+      &ModuleGraphOptions {
+        stop_at: Some(2),
+        ..ModuleGraphOptions::default()
+      },
+    )
+    .unwrap();
+    assert_eq!(breakdown.union.count, 2);
+    assert!(breakdown.entries.iter().all(|entry| entry.exceeded));
+  }
+
+  #[test]
+  fn test_analyzer_caches_parsed_modules() {
+    let base_path = fixture("vue");
+    let analyzer =
+      ModuleGraphAnalyzer::new(vec![], vec![], ResolverOptions::default(), None).unwrap();
+
+    assert_eq!(
+      analyzer
+        .count(vec!["./index.js".to_string()], base_path.clone())
+        .unwrap(),
+      4
+    );
+    let stats = analyzer.cache_stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (0, 4, 4));
+
+    let graph = analyzer
+      .count_modules(vec!["./App.vue".to_string()], base_path.clone())
+      .unwrap();
+    assert_eq!(graph.modules, vec!["App.vue", "Button.vue", "label.ts"]);
+    let stats = analyzer.cache_stats();
+    assert_eq!((stats.hits, stats.misses), (3, 4));
+
+    analyzer.clear_cache();
+    assert_eq!(analyzer.cache_stats().entries, 0);
+  }
+
+  #[test]
+  fn test_analyzer_recounts_invalidated_graphs() {
+    let base_path = fixture("vue");
+    let analyzer =
+      ModuleGraphAnalyzer::new(vec![], vec![], ResolverOptions::default(), None).unwrap();
+    let recount = |entry_point: &str| {
+      analyzer
+        .recount(vec![entry_point.to_string()], base_path.clone())
+        .unwrap()
+        .count
+    };
+
+    assert_eq!(recount("./index.js"), 4);
+    assert_eq!(recount("./App.vue"), 3);
+    let stats = analyzer.cache_stats();
+    assert_eq!((stats.hits, stats.misses), (3, 4));
+    // A graph counted before is answered without walking it
+    assert_eq!(recount("./index.js"), 4);
+    assert_eq!(analyzer.cache_stats().hits, 3);
+
+    assert!(!analyzer.invalidate("missing.js".to_string()));
+    assert!(analyzer.invalidate(format!("{}/label.ts", base_path)));
+    assert_eq!(analyzer.cache_stats().entries, 3);
+    assert_eq!(recount("./index.js"), 4);
+    // Only the invalidated module is parsed again
+    let stats = analyzer.cache_stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (6, 5, 4));
+    assert_eq!(recount("./App.vue"), 3);
+    assert_eq!(analyzer.cache_stats().hits, 9);
+  }
+
+  #[test]
+  fn test_walk_unresolved_imports() {
+    let walk = |on_unresolved| {
+      try_walk_fixture(
+        "unresolved",
+        &["./index.js", "./missing-entry.js"],
+        ResolverOptions::default(),
+        ModuleGraphOptions {
+          on_unresolved: Some(on_unresolved),
+          ..ModuleGraphOptions::default()
+        },
+      )
+    };
+
+    assert!(walk(UnresolvedImports::Error).is_err());
+    let graph = walk(UnresolvedImports::Ignore).unwrap();
+    assert_eq!(graph.nodes, vec!["index.js", "a.js"]);
+    assert!(graph.unresolved.is_empty());
+
+    let graph = walk(UnresolvedImports::Collect).unwrap();
+    assert_eq!(graph.nodes, vec!["index.js", "a.js"]);
+    let unresolved: Vec<_> = graph
+      .unresolved
+      .iter()
+      .map(|import| (import.importer.as_deref(), import.specifier.as_str()))
+      .collect();
+    assert_eq!(
+      unresolved,
+      vec![
+        (None, "./missing-entry.js"),
+        (Some("index.js"), "optional-peer"),
+        (Some("a.js"), "./missing.js")
+      ]
+    );
+  }
+
+  #[test]
+  fn test_walk_unknown_extensions() {
+    let walk = |unknown_extensions| {
+      walk_fixture(
+        "unknown-extensions",
+        ModuleGraphOptions {
+          unknown_extensions,
+          // logo.svg is walked as a module with an unknown extension
+          asset_extensions: Some(Vec::new()),
+          ..ModuleGraphOptions::default()
+        },
+      )
+    };
+
+    let graph = walk(None);
+    assert_eq!(graph.nodes, vec!["index.js", "cli", "logo.svg"]);
+    assert_eq!(graph.warnings.len(), 2);
+    assert_eq!(graph.warnings[0].module, "cli");
+
+    let graph = walk(Some(UnknownExtensions::Parse));
+    assert_eq!(
+      graph.nodes,
+      vec!["index.js", "cli", "logo.svg", "helper.js"]
+    );
+    assert_eq!(
+      graph.warnings[0].message,
+      "Unknown extension, parsed as an ES module"
+    );
+  }
+
+  #[test]
+  fn test_walk_ignores_specifiers() {
+    let walk = |ignore_specifiers: &[&str]| {
+      try_walk_fixture(
+        "unresolved",
+        &["./index.js"],
+        ResolverOptions::default(),
+        ModuleGraphOptions {
+          ignore_specifiers: Some(
+            ignore_specifiers
+              .iter()
+              .map(|pattern| pattern.to_string())
+              .collect(),
+          ),
+          ..ModuleGraphOptions::default()
+        },
+      )
+    };
+
+    let graph = walk(&["^optional-", "missing\\.js$"]).unwrap();
+    assert_eq!(graph.nodes, vec!["index.js", "a.js"]);
+    assert!(walk(&["^optional-"]).is_err());
+    assert!(walk(&["("]).is_err());
+  }
+
+  #[test]
+  fn test_walk_skips_type_only_imports() {
+    let walk = |skip_type_only_imports| {
+      try_walk_fixture(
+        "type-imports",
+        &["./index.ts"],
+        ResolverOptions {
+          extensions: Some(vec![".ts".to_string()]),
+          ..ResolverOptions::default()
+        },
+        ModuleGraphOptions {
+          skip_type_only_imports: Some(skip_type_only_imports),
+          ..ModuleGraphOptions::default()
+        },
+      )
+      .unwrap()
+    };
+
+    let graph = walk(true);
+    assert_eq!(graph.nodes, vec!["index.ts", "light.ts"]);
+    assert_eq!(graph.edges.len(), 1);
+    assert_eq!(
+      walk(false).nodes,
+      vec!["index.ts", "heavy.ts", "props.ts", "light.ts"]
+    );
+  }
+
+  #[test]
+  fn test_walk_import_attributes_as_leaves() {
+    let graph = walk_fixture(
+      "import-attributes",
+      ModuleGraphOptions {
+        import_attribute_weight: Some(0.5),
+        ..ModuleGraphOptions::default()
+      },
+    );
+    assert_eq!(
+      graph.nodes,
+      vec!["index.js", "data.json", "styles.css", "helper.js"]
+    );
+    assert_eq!(graph.size, 3.0);
+    // Counted without being parsed
+    assert!(graph.warnings.is_empty());
+  }
+
+  #[test]
+  fn test_module_graph_dot() {
+    let graph = try_walk_fixture(
+      "barrel",
+      &["./index.ts"],
+      ResolverOptions {
+        extensions: Some(vec![".ts".to_string()]),
+        ..ResolverOptions::default()
+      },
+      ModuleGraphOptions {
+        barrel_options: Some(BarrelFileOptions {
+          amount_of_exports_to_consider_module_as_barrel: 1,
+          ..BarrelFileOptions::default()
+        }),
+        ..ModuleGraphOptions::default()
+      },
+    )
+    .unwrap();
+
+    assert_eq!(graph.barrels, vec!["index.ts"]);
+    assert_eq!(
+      module_graph_dot(&graph),
+      "digraph module_graph {
+  node [shape=box];
+  \"index.ts\" [style=filled, fillcolor=orange];
+  \"button.ts\";
+  \"card.ts\";
+  \"icons.ts\";
+  \"index.ts\" -> \"button.ts\";
+  \"index.ts\" -> \"card.ts\";
+  \"index.ts\" -> \"icons.ts\";
+}
+"
+    );
+  }
+
+  #[test]
+  fn test_module_graph_dot_marks_cycles() {
+    let graph = walk_fixture(
+      "cycles",
+      ModuleGraphOptions {
+        detect_cycles: Some(true),
+        ..ModuleGraphOptions::default()
+      },
+    );
+
+    let dot = module_graph_dot(&graph);
+    assert!(dot.contains("  \"b.js\" -> \"a.js\" [color=red];\n"));
+    assert_eq!(dot_id("a\"b\\c"), "\"a\\\"b\\\\c\"");
+  }
+
+  #[test]
+  fn test_module_graph_json() {
+    let base_path = fixture("cycles");
+    let graph = walk_fixture("cycles", ModuleGraphOptions::default());
+
+    let json: serde_json::Value =
+      serde_json::from_str(&module_graph_json(&graph, &base_path).unwrap()).unwrap();
+    assert_eq!(json["nodes"].as_array().unwrap().len(), graph.nodes.len());
+    assert_eq!(json["nodes"][0]["id"], "index.js");
+    assert_eq!(json["nodes"][0]["imports"], 1);
+    assert_eq!(json["nodes"][1]["importedBy"], 2);
+    assert_eq!(json["edges"][0]["importer"], "index.js");
+    assert_eq!(json["dynamicEdges"], serde_json::json!([]));
+    assert_eq!(json["totalBytes"], graph.total_bytes);
+  }
+
+  #[test]
+  fn test_walk_depth() {
+    let depth = |max_depth| {
+      walk_fixture(
+        "cycles",
+        ModuleGraphOptions {
+          max_depth,
+          ..ModuleGraphOptions::default()
+        },
+      )
+      .depth
+    };
+
+    // index.js -> a.js -> b.js, b.js importing index.js and a.js again
+    assert_eq!(depth(None), 2);
+    assert_eq!(depth(Some(1)), 1);
+    assert_eq!(depth(Some(0)), 0);
+  }
+
+  #[test]
+  fn test_walk_dedupes_real_paths() {
+    let walk = |dedupe_real_paths| {
+      try_walk_fixture(
+        "symlink-dedupe",
+        &["./index.js"],
+        ResolverOptions {
+          symlinks: Some(false),
+          ..ResolverOptions::default()
+        },
+        ModuleGraphOptions {
+          dedupe_real_paths: Some(dedupe_real_paths),
+          ..ModuleGraphOptions::default()
+        },
+      )
+      .unwrap()
+    };
+
+    let graph = walk(true);
+    assert_eq!(graph.nodes, vec!["index.js", "shared/index.js"]);
+    let importees: Vec<_> = graph.edges.iter().map(|edge| &edge.importee).collect();
+    assert_eq!(importees, vec!["shared/index.js", "shared/index.js"]);
+    assert_eq!(
+      walk(false).nodes,
+      vec!["index.js", "shared/index.js", "linked/index.js"]
+    );
+  }
+
+  #[test]
+  fn test_walk_most_imported() {
+    let most_imported = |most_imported| {
+      walk_fixture(
+        "cycles",
+        ModuleGraphOptions {
+          most_imported,
+          ..ModuleGraphOptions::default()
+        },
+      )
+      .most_imported
+      .into_iter()
+      .map(|module| (module.module, module.importers))
+      .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+      most_imported(Some(2)),
+      vec![("a.js".to_string(), 2), ("index.js".to_string(), 1)]
+    );
+    assert!(most_imported(None).is_empty());
+  }
+
+  #[test]
+  fn test_walk_cancellation() {
+    let walk =
+      |options| try_walk_fixture("vue", &["./index.js"], ResolverOptions::default(), options);
+
+    let cancellation = CancellationFlag::default();
+    let flag = cancellation.clone();
+    let error = walk(ModuleGraphOptions {
+      cancellation: Some(cancellation.clone()),
+      // Cancels while the first module is visited
+      on_progress: Some(ProgressCallback(Arc::new(move |_| {
+        flag.0.store(true, Ordering::Relaxed);
+      }))),
+      progress_interval: Some(1),
+      ..ModuleGraphOptions::default()
+    })
+    .err()
+    .unwrap();
+    assert_eq!(error.status, Status::Cancelled);
+
+    let result = walk(ModuleGraphOptions {
+      cancellation: Some(CancellationFlag::default()),
+      ..ModuleGraphOptions::default()
+    });
+    assert_eq!(result.unwrap().nodes.len(), 4);
+  }
+
+  #[test]
+  fn test_walk_is_deterministic() {
+    let walk = |concurrency| {
+      let graph = try_walk_fixture(
+        "barrel",
+        &["./index.ts"],
+        ResolverOptions {
+          extensions: Some(vec![".ts".to_string()]),
+          ..ResolverOptions::default()
+        },
+        ModuleGraphOptions {
+          concurrency: Some(concurrency),
+          most_imported: Some(10),
+          ..ModuleGraphOptions::default()
+        },
+      )
+      .unwrap();
+      let edges: Vec<_> = graph
+        .edges
+        .into_iter()
+        .map(|edge| (edge.importer, edge.importee, edge.specifier))
+        .collect();
+      let most_imported: Vec<_> = graph
+        .most_imported
+        .into_iter()
+        .map(|module| module.module)
+        .collect();
+      (graph.nodes, edges, most_imported)
+    };
+
+    let expected = walk(1);
+    assert_eq!(
+      expected.0,
+      vec!["index.ts", "button.ts", "card.ts", "icons.ts"]
+    );
+    for concurrency in [1, 2, 3, 8, 1, 2, 3, 8] {
+      assert_eq!(walk(concurrency), expected);
+    }
+  }
+
+  #[test]
+  fn test_walk_max_file_size() {
+    let graph = walk_fixture(
+      "cycles",
+      ModuleGraphOptions {
+        max_file_size: Some(60),
+        ..ModuleGraphOptions::default()
+      },
+    );
+
+    // b.js is over the limit, its imports are left out
+    assert!(fixture_bytes("cycles", &["b.js"]) > 60);
+    assert_eq!(graph.nodes, vec!["index.js", "a.js", "b.js"]);
+    assert_eq!(graph.edges.len(), 2);
+    assert_eq!(
+      graph.total_bytes,
+      fixture_bytes("cycles", &["index.js", "a.js", "b.js"])
+    );
+    assert_eq!(graph.warnings.len(), 1);
+    assert_eq!(graph.warnings[0].module, "b.js");
+  }
+
+  #[test]
+  fn test_prefetch_requeues_panicked_parses() {
+    let resolver = create_resolver(ResolverOptions::default()).unwrap();
+    let options = ModuleGraphOptions::default();
+    let walk = Walk {
+      base_path: "",
+      builtin_modules: &[],
+      resolver: &resolver,
+      options: &options,
+      ignore_specifiers: RegexSet::empty(),
+    };
+    let prefetch = Prefetch {
+      walk: &walk,
+      cache: None,
+      parses: &|_, _, _| true,
+      follow_dynamic_imports: false,
+      tracks_depth: false,
+      max_modules: None,
+      deadline: None,
+      cancellation: None,
+      modules: Mutex::default(),
+      parsed: Condvar::new(),
+      buffers: Mutex::default(),
+      stopped: AtomicBool::new(false),
+    };
+    let dep = Path::new(&fixture("vue")).join("label.ts");
+    prefetch.modules.lock().unwrap().insert(
+      dep.clone(),
+      Prefetched {
+        depth: 0,
+        state: PrefetchState::Parsing,
+        imports: Vec::new(),
+      },
+    );
+
+    let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+      let _requeue = RequeueOnPanic {
+        prefetch: &prefetch,
+        dep: &dep,
+      };
+      panic!("parse panicked");
+    }));
+    assert!(panicked.is_err());
+    // The walk parses the module itself rather than waiting on the thread that panicked
+    assert!(prefetch.take(None, &dep).is_ok());
+  }
+
+  #[test]
+  fn test_thread_pools_are_reused() {
+    let pool = thread_pool(3).unwrap();
+    assert_eq!(pool.current_num_threads(), 3);
+    assert!(Arc::ptr_eq(&pool, &thread_pool(3).unwrap()));
+    assert!(!Arc::ptr_eq(&pool, &thread_pool(2).unwrap()));
+  }
+
+  #[test]
+  fn test_walk_assets() {
+    let walk = |assets| {
+      walk_fixture(
+        "unknown-extensions",
+        ModuleGraphOptions {
+          assets,
+          ..ModuleGraphOptions::default()
+        },
+      )
+    };
+    let logo_bytes = fixture_bytes("unknown-extensions", &["logo.svg"]);
+
+    let graph = walk(None);
+    assert_eq!(graph.nodes, vec!["index.js", "cli", "logo.svg"]);
+    // Only cli has an unknown extension
+    assert_eq!(graph.warnings.len(), 1);
+    assert_eq!(graph.size, 3.0);
+    assert_eq!(
+      walk(Some(AssetImports::Bytes)).size,
+      2.0 + logo_bytes as f64
+    );
+    let graph = walk(Some(AssetImports::Skip));
+    assert_eq!(graph.nodes, vec!["index.js", "cli"]);
+    assert!(graph.edges.iter().all(|edge| edge.importee != "logo.svg"));
+  }
+
+  #[test]
+  fn test_walk_json_modules() {
+    let walk = |json_modules| {
+      walk_fixture(
+        "json-modules",
+        ModuleGraphOptions {
+          json_modules,
+          ..ModuleGraphOptions::default()
+        },
+      )
+    };
+
+    let graph = walk(None);
+    assert_eq!(graph.nodes, vec!["index.js", "config.json"]);
+    assert!(graph.warnings.is_empty());
+    assert_eq!(
+      graph.total_bytes,
+      fixture_bytes("json-modules", &["index.js", "config.json"])
+    );
+    assert_eq!(
+      walk(Some(AssetImports::Bytes)).size,
+      1.0 + fixture_bytes("json-modules", &["config.json"]) as f64
+    );
+    assert_eq!(walk(Some(AssetImports::Skip)).nodes, vec!["index.js"]);
+  }
+
+  #[test]
+  fn test_walk_tolerates_parse_errors() {
+    let graph = walk_fixture("parse-errors", ModuleGraphOptions::default());
+
+    // never.js is only imported by broken.js
+    assert_eq!(graph.nodes, vec!["index.js", "broken.js", "ok.js"]);
+    assert_eq!(graph.diagnostics.len(), 1);
+    assert_eq!(graph.diagnostics[0].module, "broken.js");
+    assert_eq!(graph.diagnostics[0].errors[0].loc.start.line, 3);
+  }
+
+  #[test]
+  fn test_walk_through_shebangs_and_boms() {
+    let graph = try_walk_fixture(
+      "shebang",
+      &["./cli.js", "./bin"],
+      ResolverOptions::default(),
+      ModuleGraphOptions {
+        unknown_extensions: Some(UnknownExtensions::Parse),
+        follow_require_calls: Some(true),
+        ..ModuleGraphOptions::default()
+      },
+    )
+    .unwrap();
+
+    // cli.js and bin start with `#!/usr/bin/env node`, bom.js with a UTF-8 BOM
+    assert_eq!(graph.nodes, vec!["cli.js", "bin", "bom.js", "helper.js"]);
+    assert!(graph.diagnostics.is_empty());
+    assert_eq!(graph.edges.len(), 3);
+  }
+}
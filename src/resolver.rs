@@ -0,0 +1,1820 @@
+//! Module resolution shared by the `resolve*` entry points and the module graph walker.
+
+use napi::Status::GenericFailure;
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, Error, JsObject, Result, Task};
+use napi_derive::napi;
+use oxc_resolver::{
+  AliasValue, CompilerOptions, FsResolution, PackageJson, PackageJsonSerde, PathUtil,
+  ProjectReference, ResolveError, ResolveOptions, Resolver, TsConfig, TsConfigSerde,
+  TsconfigOptions, TsconfigReferences,
+};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Component, MAIN_SEPARATOR, Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[napi(object, object_to_js = false)]
+#[derive(Clone, Default)]
+pub struct TsconfigResolveOptions {
+  /// Path to the `tsconfig.json` or `jsconfig.json` to read `paths` and `baseUrl` from, or
+  /// to the directory containing it
+  pub config_file: String,
+  /// Project references to load, defaults to the `references` field of `config_file`
+  pub references: Option<Vec<String>>,
+}
+
+/// Options forwarded to oxc_resolver.
+///
+/// Alternate manifest names are not supported, there is no `description_files` option.
+/// Manifests are always read from `package.json`, oxc_resolver hardcodes the file name
+/// and has no equivalent of enhanced-resolve's `descriptionFiles`.
+#[napi(object, object_to_js = false)]
+#[derive(Clone, Default)]
+pub struct ResolverOptions {
+  /// Condition names for the `exports` field, e.g. `["node", "import"]`
+  pub condition_names: Option<Vec<String>>,
+  /// Fields in `package.json` to read the entry point from, e.g. `["module", "main"]`
+  pub main_fields: Option<Vec<String>>,
+  /// Extensions to try, in order, e.g. `[".js", ".ts"]`
+  pub extensions: Option<Vec<String>>,
+  pub tsconfig: Option<TsconfigResolveOptions>,
+  /// Aliases as `[key, targets]` pairs, an empty `targets` ignores the module
+  pub alias: Option<Vec<(String, Vec<String>)>>,
+  /// Path to the `.pnp.cjs` manifest of a Yarn Plug'n'Play project
+  pub pnp_manifest: Option<String>,
+  /// Fields in `package.json` that map `#internal` specifiers, defaults to `[["imports"]]`
+  pub imports_fields: Option<Vec<Vec<String>>>,
+  /// Whether to resolve symlinks to their real path, defaults to `true`
+  pub symlinks: Option<bool>,
+  /// Extensions to try in place of an explicit one, e.g. `[[".js", [".ts", ".js"]]]`
+  pub extension_alias: Option<Vec<(String, Vec<String>)>>,
+  /// Directories to look up bare specifiers in, defaults to `["node_modules"]`
+  pub modules: Option<Vec<String>>,
+  /// Directories that root-relative specifiers such as `/src/button` are resolved against
+  pub roots: Option<Vec<String>>,
+  /// Fields in `package.json` that remap files, such as `[["browser"]]`, ignored by default
+  pub alias_fields: Option<Vec<Vec<String>>>,
+  /// Disable extension and directory index guessing for ESM-style resolution, defaults to `false`
+  pub fully_specified: Option<bool>,
+  /// Absolute directories resolved paths must stay inside of, e.g. the workspace root
+  pub restrictions: Option<Vec<String>>,
+  /// Try `utils/foo` as a relative path before looking it up in `modules`, defaults to `false`
+  pub prefer_relative: Option<bool>,
+  /// Separator used in returned paths, defaults to `native`
+  pub path_separator: Option<PathSeparator>,
+  /// Aliases tried only after normal resolution failed, e.g. `[["crypto", ["crypto-browserify"]]]`
+  pub fallback: Option<Vec<(String, Vec<String>)>>,
+  /// File names tried for directory imports, without extension, defaults to `["index"]`
+  pub main_files: Option<Vec<String>>,
+  /// Reject relative and absolute specifiers that leave out the file extension or point
+  /// at a directory, reported as `MissingExtension`, defaults to `false`
+  pub enforce_extension: Option<bool>,
+  /// Use the nearest `tsconfig.json` or `jsconfig.json` above each importer, outside of
+  /// `node_modules`, in place of `tsconfig`, defaults to `false`
+  pub discover_tsconfig: Option<bool>,
+  /// Report resolved paths whose casing differs from the files on disk, which break on
+  /// case-sensitive filesystems, as `CaseMismatch`, defaults to `false`
+  pub case_sensitive: Option<bool>,
+  /// Resolve files inside the `outDir` of a referenced tsconfig project to the sources they
+  /// are built from, the way TypeScript follows project references, defaults to `false`
+  pub prefer_reference_source: Option<bool>,
+}
+
+#[napi(string_enum)]
+pub enum PathSeparator {
+  /// `\` on Windows and `/` everywhere else
+  #[napi(value = "native")]
+  Native,
+  /// `/` on every platform, matching ESLint's `context.filename` on Windows
+  #[napi(value = "slash")]
+  Slash,
+}
+
+impl ResolverOptions {
+  pub fn into_resolve_options(self) -> Result<ResolveOptions> {
+    let pnp_manifest = match self.pnp_manifest {
+      None => None,
+      Some(manifest_path) => match pnp::load_pnp_manifest(&manifest_path) {
+        Ok(manifest) => Some(manifest),
+        Err(pnp_error) => {
+          return Err(Error::new(
+            GenericFailure,
+            format!(
+              "Failed to load pnp manifest: \"{}\", message: \"{}\"",
+              &manifest_path,
+              pnp_error.to_string()
+            ),
+          ));
+        }
+      },
+    };
+
+    let defaults = ResolveOptions::default();
+    Ok(ResolveOptions {
+      tsconfig: self
+        .tsconfig
+        .map(|tsconfig| create_tsconfig_option(tsconfig.config_file, tsconfig.references)),
+      condition_names: self.condition_names.unwrap_or(defaults.condition_names),
+      main_fields: self.main_fields.unwrap_or(defaults.main_fields),
+      extensions: self.extensions.unwrap_or(defaults.extensions),
+      alias: create_alias_option(self.alias.unwrap_or_default()),
+      imports_fields: self.imports_fields.unwrap_or(defaults.imports_fields),
+      symlinks: self.symlinks.unwrap_or(defaults.symlinks),
+      extension_alias: self.extension_alias.unwrap_or_default(),
+      modules: self.modules.unwrap_or(defaults.modules),
+      roots: self
+        .roots
+        .map(|roots| roots.into_iter().map(PathBuf::from).collect())
+        .unwrap_or(defaults.roots),
+      alias_fields: self.alias_fields.unwrap_or(defaults.alias_fields),
+      fully_specified: self.fully_specified.unwrap_or(defaults.fully_specified),
+      prefer_relative: self.prefer_relative.unwrap_or(defaults.prefer_relative),
+      builtin_modules: true,
+      fallback: create_alias_option(self.fallback.unwrap_or_default()),
+      main_files: self.main_files.unwrap_or(defaults.main_files),
+      pnp_manifest,
+      ..defaults
+    })
+  }
+}
+
+/// oxc_resolver's `Resolver` plus the checks this crate applies to its resolutions.
+pub struct ModuleResolver {
+  resolver: Arc<Resolver>,
+  /// Directories a resolution has to stay inside of, checked here since oxc_resolver's
+  /// own `restrictions` only accept the restricted directory itself
+  restrictions: Vec<PathBuf>,
+  path_separator: PathSeparator,
+  /// oxc_resolver reports builtins before it tries `fallback`, so when fallbacks are
+  /// configured builtins are retried with this resolver, which treats them as bare specifiers
+  builtin_fallback: Option<Resolver>,
+  /// Whether relative and absolute specifiers have to name the file they resolve to,
+  /// checked here since oxc_resolver's `enforce_extension` still appends `extensions`
+  enforce_extension: bool,
+  /// With `discover_tsconfig`, the nearest tsconfig of each importer directory and a
+  /// resolver per tsconfig, sharing the filesystem cache of `resolver`
+  tsconfig_resolvers: Option<Mutex<TsconfigResolvers>>,
+  /// Whether resolved paths have to match the casing on disk, which case-insensitive
+  /// filesystems such as the macOS and Windows defaults don't enforce themselves
+  case_sensitive: bool,
+  /// With `prefer_reference_source`, the output and source directories of each referenced
+  /// tsconfig, read here since oxc_resolver parses neither `outDir` nor `rootDir`
+  project_outputs: Option<Mutex<HashMap<PathBuf, Option<Arc<ProjectOutput>>>>>,
+}
+
+#[derive(Default)]
+struct TsconfigResolvers {
+  by_directory: HashMap<PathBuf, Option<PathBuf>>,
+  by_tsconfig: HashMap<PathBuf, Arc<Resolver>>,
+}
+
+/// Why `ModuleResolver::resolve` failed.
+#[derive(Clone, Debug)]
+pub enum ResolveFailure {
+  Resolver(ResolveError),
+  /// With `enforce_extension`, the specifier lacks the extension of the path it resolves to
+  MissingExtension(PathBuf),
+  /// With `case_sensitive`, the resolved path differs in casing from the one on disk
+  CaseMismatch {
+    path: PathBuf,
+    actual: PathBuf,
+  },
+}
+
+impl From<ResolveError> for ResolveFailure {
+  fn from(error: ResolveError) -> Self {
+    Self::Resolver(error)
+  }
+}
+
+impl std::fmt::Display for ResolveFailure {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      // oxc_resolver prints the resolved path twice for restriction errors
+      Self::Resolver(ResolveError::Restriction(path, restriction)) => write!(
+        f,
+        "Path \"{}\" is outside of \"{}\"",
+        path.display(),
+        restriction.display()
+      ),
+      Self::Resolver(error) => error.fmt(f),
+      Self::CaseMismatch { path, actual } => write!(
+        f,
+        "Case mismatch, \"{}\" is \"{}\" on disk",
+        path.display(),
+        actual.display()
+      ),
+      Self::MissingExtension(path) => {
+        write!(
+          f,
+          "Missing file extension, resolves to \"{}\"",
+          path.display()
+        )
+      }
+    }
+  }
+}
+
+/// What a specifier resolved to.
+#[derive(Clone)]
+pub enum Resolved {
+  /// A file on disk
+  Module(FsResolution),
+  /// A Node builtin, by its `node:` prefixed name
+  Builtin(String),
+  /// A URL specifier such as `https://esm.sh/react`, left to the runtime to load
+  Url(String),
+}
+
+impl ModuleResolver {
+  pub fn resolve<P: AsRef<Path>>(
+    &self,
+    directory: P,
+    specifier: &str,
+  ) -> std::result::Result<Resolved, ResolveFailure> {
+    if is_url_specifier(specifier) {
+      return Ok(Resolved::Url(specifier.to_string()));
+    }
+    let resolution = match self
+      .resolver_for(directory.as_ref())
+      .resolve(&directory, specifier)
+    {
+      Ok(resolution) => resolution,
+      Err(ResolveError::Builtin { resolved, .. }) => {
+        match self
+          .builtin_fallback
+          .as_ref()
+          .and_then(|fallback| fallback.resolve(&directory, specifier).ok())
+        {
+          Some(resolution) => resolution,
+          None => return Ok(Resolved::Builtin(resolved)),
+        }
+      }
+      Err(resolve_error) => return Err(resolve_error.into()),
+    };
+    let resolution = self
+      .reference_source(directory.as_ref(), resolution.path())
+      .unwrap_or(resolution);
+    if let Some(restriction) = self
+      .restrictions
+      .iter()
+      .find(|restriction| !resolution.path().starts_with(restriction))
+    {
+      return Err(
+        ResolveError::Restriction(resolution.path().to_path_buf(), restriction.clone()).into(),
+      );
+    }
+    if self.enforce_extension && is_missing_extension(specifier, resolution.path()) {
+      return Err(ResolveFailure::MissingExtension(resolution.into_path_buf()));
+    }
+    if self.case_sensitive
+      && let Some(actual) = case_mismatch(directory.as_ref(), resolution.path())
+    {
+      return Err(ResolveFailure::CaseMismatch {
+        path: resolution.into_path_buf(),
+        actual,
+      });
+    }
+    Ok(Resolved::Module(resolution))
+  }
+
+  pub fn clear_cache(&self) {
+    self.resolver.clear_cache();
+    if let Some(tsconfig_resolvers) = &self.tsconfig_resolvers {
+      *tsconfig_resolvers.lock().unwrap() = TsconfigResolvers::default();
+    }
+    if let Some(project_outputs) = &self.project_outputs {
+      project_outputs.lock().unwrap().clear();
+    }
+  }
+
+  /// With `prefer_reference_source`, the source file that `path` is built from when it lies
+  /// inside the `outDir` of a project referenced by the tsconfig of `directory`.
+  fn reference_source(&self, directory: &Path, path: &Path) -> Option<FsResolution> {
+    let project_outputs = self.project_outputs.as_ref()?;
+    let resolver = self.resolver_for(directory);
+    let tsconfig_options = resolver.options().tsconfig.as_ref()?;
+    let tsconfig = resolver
+      .resolve_tsconfig(&tsconfig_options.config_file)
+      .ok()?;
+    let references: Vec<PathBuf> = match &tsconfig_options.references {
+      TsconfigReferences::Disabled => Vec::new(),
+      TsconfigReferences::Auto => tsconfig
+        .references()
+        .filter_map(|reference| reference.tsconfig())
+        .map(|reference| reference.path().to_path_buf())
+        .collect(),
+      TsconfigReferences::Paths(paths) => paths
+        .iter()
+        .map(|reference| tsconfig.directory().normalize_with(reference))
+        .filter_map(|reference| {
+          if reference.is_dir() {
+            tsconfig_in(&reference)
+          } else {
+            Some(reference)
+          }
+        })
+        .collect(),
+    };
+    let outputs: Vec<Arc<ProjectOutput>> = {
+      let mut project_outputs = project_outputs.lock().unwrap();
+      references
+        .into_iter()
+        .filter_map(|reference| {
+          project_outputs
+            .entry(reference)
+            .or_insert_with_key(|reference| ProjectOutput::read(reference).map(Arc::new))
+            .clone()
+        })
+        .collect()
+    };
+    outputs.iter().find_map(|output| {
+      let source = output.source_for(path)?;
+      self
+        .resolver
+        .resolve(source.parent()?, source.to_str()?)
+        .ok()
+    })
+  }
+
+  /// The resolver for specifiers imported from `directory`, configured with its nearest
+  /// tsconfig when `discover_tsconfig` is set.
+  fn resolver_for(&self, directory: &Path) -> Arc<Resolver> {
+    let Some(tsconfig_resolvers) = &self.tsconfig_resolvers else {
+      return Arc::clone(&self.resolver);
+    };
+    let mut tsconfig_resolvers = tsconfig_resolvers.lock().unwrap();
+    let tsconfig = tsconfig_resolvers
+      .by_directory
+      .entry(directory.to_path_buf())
+      .or_insert_with(|| find_tsconfig(directory))
+      .clone();
+    let Some(tsconfig) = tsconfig else {
+      return Arc::clone(&self.resolver);
+    };
+    let resolver = tsconfig_resolvers
+      .by_tsconfig
+      .entry(tsconfig)
+      .or_insert_with_key(|tsconfig| {
+        Arc::new(self.resolver.clone_with_options(ResolveOptions {
+          tsconfig: Some(create_tsconfig_option(
+            tsconfig.to_string_lossy().into_owned(),
+            None,
+          )),
+          ..self.resolver.options().clone()
+        }))
+      });
+    Arc::clone(resolver)
+  }
+
+  /// Turns a resolution into the resolved path, Node builtins resolve to their `node:`
+  /// prefixed name and URLs to themselves.
+  fn resolved_path(
+    &self,
+    resolution: std::result::Result<Resolved, ResolveFailure>,
+    importer: &str,
+    importee: &str,
+  ) -> std::result::Result<String, ResolutionError> {
+    match resolution {
+      Ok(Resolved::Module(resolution)) => Ok(self.path_string(resolution.path())),
+      Ok(Resolved::Builtin(resolved)) => Ok(resolved),
+      Ok(Resolved::Url(url)) => Ok(url),
+      Err(resolve_error) => Err(ResolutionError::new(&resolve_error, importer, importee)),
+    }
+  }
+
+  /// Finds the `package.json` owning `path`, the package root for files inside
+  /// `node_modules` and the nearest one otherwise.
+  pub fn package_for_path(&self, path: &str) -> Option<PackageInfo> {
+    let resolution = self.resolver.resolve(importer_directory(path), path).ok()?;
+    let package_json = resolution.package_json()?;
+    Some(PackageInfo {
+      name: package_json.name().map(String::from),
+      version: package_version(package_json),
+      package_json_path: self.path_string(package_json.path()),
+    })
+  }
+
+  pub fn path_string(&self, path: &Path) -> String {
+    normalize_separators(
+      path.to_str().unwrap().to_string(),
+      self.path_separator,
+      MAIN_SEPARATOR,
+    )
+  }
+
+  fn resolution_details(
+    &self,
+    resolution: std::result::Result<Resolved, ResolveFailure>,
+    importer: &str,
+    importee: &str,
+  ) -> std::result::Result<ResolutionDetails, ResolutionError> {
+    let resolution = match resolution {
+      Ok(Resolved::Module(resolution)) => resolution,
+      Ok(Resolved::Builtin(resolved)) => return Ok(ResolutionDetails::external(resolved, true)),
+      Ok(Resolved::Url(url)) => return Ok(ResolutionDetails::external(url, false)),
+      Err(resolve_error) => return Err(ResolutionError::new(&resolve_error, importer, importee)),
+    };
+    let tsconfig_paths =
+      self.match_tsconfig_paths(importer_directory(importer), importee, resolution.path());
+    let details = ResolutionDetails::from(resolution);
+    Ok(ResolutionDetails {
+      path: self.path_string(Path::new(&details.path)),
+      package_json_path: details
+        .package_json_path
+        .as_deref()
+        .map(|package_json_path| self.path_string(Path::new(package_json_path))),
+      tsconfig_paths,
+      ..details
+    })
+  }
+
+  /// Finds the `compilerOptions.paths` entry that `specifier` was resolved through, using
+  /// the same tsconfig selection and pattern precedence as oxc_resolver.
+  fn match_tsconfig_paths(
+    &self,
+    directory: &Path,
+    specifier: &str,
+    resolved: &Path,
+  ) -> Option<TsconfigPathsMatch> {
+    if specifier.starts_with(['/', '.']) {
+      return None;
+    }
+    let resolver = self.resolver_for(directory);
+    let tsconfig_options = resolver.options().tsconfig.as_ref()?;
+    let tsconfig = resolver
+      .resolve_tsconfig(&tsconfig_options.config_file)
+      .ok()?;
+    let mut tsconfigs = vec![Arc::clone(&tsconfig)];
+    tsconfigs.extend(
+      tsconfig
+        .references()
+        .filter_map(|reference| reference.tsconfig()),
+    );
+
+    tsconfigs
+      .iter()
+      .filter(|tsconfig| directory.starts_with(tsconfig.base_path()))
+      .find_map(|tsconfig| match_paths(&resolver, tsconfig, specifier, resolved))
+  }
+}
+
+/// Finds the `paths` pattern of `tsconfig` that `specifier` matches and the first of its
+/// targets that resolves to `resolved`.
+fn match_paths(
+  resolver: &Resolver,
+  tsconfig: &TsConfigSerde,
+  specifier: &str,
+  resolved: &Path,
+) -> Option<TsconfigPathsMatch> {
+  let compiler_options = tsconfig.compiler_options();
+  let paths = compiler_options.paths()?;
+  let (pattern, wildcard) = match paths.get_key_value(specifier) {
+    Some((pattern, _)) => (pattern, ""),
+    // The longest prefix wins, the first one declared on ties
+    None => paths
+      .keys()
+      .rev()
+      .filter_map(|pattern| {
+        let (prefix, suffix) = pattern.split_once('*')?;
+        let wildcard = specifier.strip_prefix(prefix)?.strip_suffix(suffix)?;
+        Some((pattern, prefix.len(), wildcard))
+      })
+      .max_by_key(|(_, prefix_length, _)| *prefix_length)
+      .map(|(pattern, _, wildcard)| (pattern, wildcard))?,
+  };
+
+  let paths_base = compiler_options.paths_base();
+  paths[pattern]
+    .iter()
+    .find(|target| {
+      let candidate = paths_base.join(target.replace('*', wildcard));
+      candidate.to_str().is_some_and(|candidate| {
+        resolver
+          .resolve(paths_base, candidate)
+          .is_ok_and(|resolution| resolution.path() == resolved)
+      })
+    })
+    .map(|target| TsconfigPathsMatch {
+      pattern: pattern.clone(),
+      target: target.clone(),
+    })
+}
+
+/// A resolver that is constructed once and keeps oxc_resolver's filesystem cache
+/// alive between calls.
+#[napi]
+pub struct ResolverHandle {
+  resolver: Arc<ModuleResolver>,
+  cache: Arc<ResolutionCache>,
+}
+
+#[napi]
+impl ResolverHandle {
+  #[napi(constructor)]
+  pub fn new(options: ResolverOptions) -> Result<Self> {
+    Ok(Self {
+      resolver: Arc::new(create_resolver(options)?),
+      cache: Arc::default(),
+    })
+  }
+
+  #[napi]
+  pub fn resolve(&self, env: Env, importer: String, importee: String) -> Result<String> {
+    let resolution = self
+      .cache
+      .resolve(&self.resolver, importer_directory(&importer), &importee);
+    self
+      .resolver
+      .resolved_path(resolution, &importer, &importee)
+      .map_err(|error| error.into_napi_error(&env))
+  }
+
+  #[napi]
+  pub fn resolve_many(&self, importer: String, importees: Vec<String>) -> Vec<ResolveManyResult> {
+    importees
+      .iter()
+      .map(|importee| {
+        let resolution =
+          self
+            .cache
+            .resolve(&self.resolver, importer_directory(&importer), importee);
+        self
+          .resolver
+          .resolved_path(resolution, &importer, importee)
+          .into()
+      })
+      .collect()
+  }
+
+  #[napi]
+  pub fn resolve_with_details(
+    &self,
+    env: Env,
+    importer: String,
+    importee: String,
+  ) -> Result<ResolutionDetails> {
+    let resolution = self
+      .cache
+      .resolve(&self.resolver, importer_directory(&importer), &importee);
+    self
+      .resolver
+      .resolution_details(resolution, &importer, &importee)
+      .map_err(|error| error.into_napi_error(&env))
+  }
+
+  /// Resolves on the libuv thread pool, sharing this handle's cache.
+  #[napi]
+  pub fn resolve_async(&self, importer: String, importee: String) -> AsyncTask<ResolveTask> {
+    AsyncTask::new(ResolveTask {
+      resolver: TaskResolver::Shared(Arc::clone(&self.resolver)),
+      cache: Arc::clone(&self.cache),
+      importer,
+      importee,
+    })
+  }
+
+  #[napi]
+  pub fn package_for_path(&self, path: String) -> Option<PackageInfo> {
+    self.resolver.package_for_path(&path)
+  }
+
+  #[napi]
+  pub fn cache_stats(&self) -> CacheStats {
+    self.cache.stats()
+  }
+
+  /// Resolves as if imported from a file inside `context_dir`.
+  #[napi]
+  pub fn resolve_from_directory(
+    &self,
+    env: Env,
+    context_dir: String,
+    importee: String,
+  ) -> Result<String> {
+    let resolution = self
+      .cache
+      .resolve(&self.resolver, Path::new(&context_dir), &importee);
+    self
+      .resolver
+      .resolved_path(resolution, &context_dir, &importee)
+      .map_err(|error| error.into_napi_error(&env))
+  }
+
+  /// Drops every cached resolution and oxc_resolver's filesystem cache, e.g. after
+  /// `node_modules` changed on disk.
+  #[napi]
+  pub fn clear_cache(&self) {
+    self.cache.clear();
+    self.resolver.clear_cache();
+  }
+}
+
+#[napi(object)]
+pub struct CacheStats {
+  /// Resolutions answered from the cache
+  pub hits: u32,
+  /// Resolutions that had to go through the resolver
+  pub misses: u32,
+  /// Distinct context directory and specifier pairs currently cached
+  pub entries: u32,
+}
+
+/// Memoizes successful resolutions by context directory and specifier, so repeated lookups
+/// skip the resolver entirely. Failures aren't memoized, though oxc_resolver's filesystem
+/// cache remembers missing paths until `clear_cache`, and the oldest entries make room once
+/// `capacity` are cached.
+pub struct ResolutionCache {
+  entries: Mutex<ResolutionEntries>,
+  capacity: usize,
+  hits: AtomicU32,
+  misses: AtomicU32,
+}
+
+#[derive(Default)]
+struct ResolutionEntries {
+  by_key: HashMap<(PathBuf, String), Resolved>,
+  /// The keys of `by_key`, oldest first
+  order: VecDeque<(PathBuf, String)>,
+}
+
+/// How many resolutions a `ResolutionCache` keeps by default.
+const RESOLUTION_CACHE_CAPACITY: usize = 50_000;
+
+impl Default for ResolutionCache {
+  fn default() -> Self {
+    Self::with_capacity(RESOLUTION_CACHE_CAPACITY)
+  }
+}
+
+impl ResolutionCache {
+  fn with_capacity(capacity: usize) -> Self {
+    Self {
+      entries: Mutex::default(),
+      capacity,
+      hits: AtomicU32::new(0),
+      misses: AtomicU32::new(0),
+    }
+  }
+
+  fn insert(&self, key: (PathBuf, String), resolved: Resolved) {
+    let mut entries = self.entries.lock().unwrap();
+    if entries.by_key.insert(key.clone(), resolved).is_some() {
+      return;
+    }
+    entries.order.push_back(key);
+    while entries.order.len() > self.capacity {
+      if let Some(oldest) = entries.order.pop_front() {
+        entries.by_key.remove(&oldest);
+      }
+    }
+  }
+
+  fn resolve(
+    &self,
+    resolver: &ModuleResolver,
+    directory: &Path,
+    importee: &str,
+  ) -> std::result::Result<Resolved, ResolveFailure> {
+    let key = (directory.to_path_buf(), importee.to_string());
+    if let Some(resolved) = self.entries.lock().unwrap().by_key.get(&key) {
+      self.hits.fetch_add(1, Ordering::Relaxed);
+      return Ok(resolved.clone());
+    }
+
+    self.misses.fetch_add(1, Ordering::Relaxed);
+    let resolution = resolver.resolve(directory, importee);
+    if let Ok(resolved) = &resolution {
+      self.insert(key, resolved.clone());
+    }
+    resolution
+  }
+
+  fn stats(&self) -> CacheStats {
+    CacheStats {
+      hits: self.hits.load(Ordering::Relaxed),
+      misses: self.misses.load(Ordering::Relaxed),
+      entries: self.entries.lock().unwrap().by_key.len() as u32,
+    }
+  }
+
+  fn clear(&self) {
+    *self.entries.lock().unwrap() = ResolutionEntries::default();
+    self.hits.store(0, Ordering::Relaxed);
+    self.misses.store(0, Ordering::Relaxed);
+  }
+}
+
+pub struct ResolveTask {
+  resolver: TaskResolver,
+  cache: Arc<ResolutionCache>,
+  importer: String,
+  importee: String,
+}
+
+/// The resolver of a task, created off the main thread from its options when the task runs,
+/// or shared with a `ResolverHandle`.
+enum TaskResolver {
+  Options(Box<ResolverOptions>),
+  Shared(Arc<ModuleResolver>),
+}
+
+impl TaskResolver {
+  fn get(&mut self) -> Result<&ModuleResolver> {
+    if let Self::Options(options) = self {
+      *self = Self::Shared(Arc::new(create_resolver(ResolverOptions::clone(options))?));
+    }
+    match self {
+      Self::Shared(resolver) => Ok(resolver),
+      Self::Options(_) => unreachable!(),
+    }
+  }
+}
+
+impl ResolveTask {
+  pub fn new(options: ResolverOptions, importer: String, importee: String) -> Self {
+    Self {
+      resolver: TaskResolver::Options(Box::new(options)),
+      cache: Arc::default(),
+      importer,
+      importee,
+    }
+  }
+}
+
+#[napi]
+impl Task for ResolveTask {
+  type Output = std::result::Result<String, ResolutionError>;
+  type JsValue = String;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let resolver = self.resolver.get()?;
+    let resolution =
+      self
+        .cache
+        .resolve(resolver, importer_directory(&self.importer), &self.importee);
+    Ok(resolver.resolved_path(resolution, &self.importer, &self.importee))
+  }
+
+  fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    output.map_err(|error| error.into_napi_error(&env))
+  }
+}
+
+#[napi(string_enum)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ResolveErrorCode {
+  /// No file matched the specifier
+  NotFound,
+  /// The tsconfig could not be found or read
+  TsconfigError,
+  /// A `package.json` `exports`/`imports` map was invalid or did not expose the specifier
+  InvalidPackageTarget,
+  /// The specifier resolved to a path outside of the `restrictions`
+  Restricted,
+  /// With `enforce_extension`, the specifier lacks the extension of the path it resolves to
+  MissingExtension,
+  /// With `case_sensitive`, the resolved path differs in casing from the one on disk
+  CaseMismatch,
+  IoError,
+  Unknown,
+}
+
+impl From<&ResolveFailure> for ResolveErrorCode {
+  fn from(failure: &ResolveFailure) -> Self {
+    match failure {
+      ResolveFailure::Resolver(error) => Self::from(error),
+      ResolveFailure::MissingExtension(_) => Self::MissingExtension,
+      ResolveFailure::CaseMismatch { .. } => Self::CaseMismatch,
+    }
+  }
+}
+
+impl From<&ResolveError> for ResolveErrorCode {
+  fn from(error: &ResolveError) -> Self {
+    match error {
+      ResolveError::NotFound(_)
+      | ResolveError::MatchedAliasNotFound(..)
+      | ResolveError::ExtensionAlias(..) => Self::NotFound,
+      ResolveError::TsconfigNotFound(_) | ResolveError::TsconfigSelfReference(_) => {
+        Self::TsconfigError
+      }
+      ResolveError::Json(json_error) if is_tsconfig_path(&json_error.path) => Self::TsconfigError,
+      ResolveError::InvalidModuleSpecifier(..)
+      | ResolveError::InvalidPackageTarget(..)
+      | ResolveError::PackagePathNotExported(..)
+      | ResolveError::InvalidPackageConfig(_)
+      | ResolveError::InvalidPackageConfigDefault(_)
+      | ResolveError::InvalidPackageConfigDirectory(_)
+      | ResolveError::PackageImportNotDefined(..) => Self::InvalidPackageTarget,
+      ResolveError::Restriction(..) => Self::Restricted,
+      ResolveError::IOError(_) => Self::IoError,
+      _ => Self::Unknown,
+    }
+  }
+}
+
+fn is_tsconfig_path(path: &Path) -> bool {
+  path
+    .file_name()
+    .and_then(|file_name| file_name.to_str())
+    .is_some_and(|file_name| file_name.starts_with("tsconfig") || file_name.starts_with("jsconfig"))
+}
+
+/// A failed resolution, thrown to JS as an `Error` carrying these fields as properties.
+#[napi(object)]
+#[derive(Debug)]
+pub struct ResolutionError {
+  pub code: ResolveErrorCode,
+  pub specifier: String,
+  /// The importing file, or the context directory when resolving from one
+  pub importer: String,
+  pub message: String,
+}
+
+impl ResolutionError {
+  fn new(failure: &ResolveFailure, importer: &str, specifier: &str) -> Self {
+    Self {
+      code: ResolveErrorCode::from(failure),
+      specifier: specifier.to_string(),
+      importer: importer.to_string(),
+      message: format!(
+        "Failed to resolve importer: \"{}\", importee: \"{}\", message: \"{}\"",
+        importer, specifier, failure
+      ),
+    }
+  }
+
+  pub fn into_napi_error(self, env: &Env) -> Error {
+    let create_js_error = || -> Result<JsObject> {
+      let mut js_error = env.create_error(Error::new(GenericFailure, self.message.clone()))?;
+      js_error.set_named_property("code", self.code)?;
+      js_error.set_named_property("specifier", self.specifier.as_str())?;
+      js_error.set_named_property("importer", self.importer.as_str())?;
+      Ok(js_error)
+    };
+    match create_js_error() {
+      Ok(js_error) => Error::from(js_error.into_unknown()),
+      Err(error) => error,
+    }
+  }
+}
+
+#[napi(object)]
+pub struct ResolveManyResult {
+  /// The resolved path, if `importee` could be resolved
+  pub path: Option<String>,
+  /// The reason resolution failed, if it did
+  pub error: Option<ResolutionError>,
+}
+
+impl From<std::result::Result<String, ResolutionError>> for ResolveManyResult {
+  fn from(result: std::result::Result<String, ResolutionError>) -> Self {
+    match result {
+      Ok(path) => Self {
+        path: Some(path),
+        error: None,
+      },
+      Err(error) => Self {
+        path: None,
+        error: Some(error),
+      },
+    }
+  }
+}
+
+#[napi(object)]
+pub struct ResolutionDetails {
+  /// The resolved path, without query and fragment
+  pub path: String,
+  /// `name` of the nearest `package.json` to the resolved path
+  pub package_name: Option<String>,
+  /// `version` of the nearest `package.json` to the resolved path
+  pub package_version: Option<String>,
+  pub package_json_path: Option<String>,
+  /// Whether the resolved path lives inside a `node_modules` directory
+  pub is_node_module: bool,
+  /// The `?query` of the importee, including the leading `?`
+  pub query: Option<String>,
+  /// The `#fragment` of the importee, including the leading `#`
+  pub fragment: Option<String>,
+  /// The tsconfig `paths` mapping the importee was resolved through
+  pub tsconfig_paths: Option<TsconfigPathsMatch>,
+  /// Whether the importee is a Node builtin, `path` is then its `node:` prefixed name
+  pub is_builtin: bool,
+  /// Whether the importee is a URL such as `https://esm.sh/react`, `path` is then the URL
+  pub is_url: bool,
+}
+
+#[napi(object)]
+pub struct PackageInfo {
+  pub name: Option<String>,
+  pub version: Option<String>,
+  pub package_json_path: String,
+}
+
+fn package_version(package_json: &PackageJsonSerde) -> Option<String> {
+  package_json
+    .raw_json()
+    .get("version")
+    .and_then(|version| version.as_str())
+    .map(String::from)
+}
+
+#[napi(object)]
+pub struct TsconfigPathsMatch {
+  /// The `compilerOptions.paths` key that matched, e.g. `@app/*`
+  pub pattern: String,
+  /// The mapped path that resolved, e.g. `src/*`
+  pub target: String,
+}
+
+impl From<FsResolution> for ResolutionDetails {
+  fn from(resolution: FsResolution) -> Self {
+    let package_json = resolution.package_json();
+    Self {
+      path: resolution.path().to_str().unwrap().to_string(),
+      package_name: package_json.and_then(|pkg| pkg.name().map(String::from)),
+      package_version: package_json.and_then(|pkg| package_version(pkg)),
+      package_json_path: package_json.map(|pkg| pkg.path().to_str().unwrap().to_string()),
+      is_node_module: resolution
+        .path()
+        .components()
+        .any(|component| component.as_os_str() == "node_modules"),
+      query: resolution.query().map(String::from),
+      fragment: resolution.fragment().map(String::from),
+      tsconfig_paths: None,
+      is_builtin: false,
+      is_url: false,
+    }
+  }
+}
+
+impl ResolutionDetails {
+  fn external(path: String, is_builtin: bool) -> Self {
+    Self {
+      is_url: !is_builtin,
+      path,
+      package_name: None,
+      package_version: None,
+      package_json_path: None,
+      is_node_module: false,
+      query: None,
+      fragment: None,
+      tsconfig_paths: None,
+      is_builtin,
+    }
+  }
+}
+
+/// Whether `specifier` starts with a URL scheme such as `https:`, `data:` or `file:`.
+///
+/// `node:` is left to the resolver's builtin handling, and single letter schemes are
+/// Windows drive letters.
+pub fn is_url_specifier(specifier: &str) -> bool {
+  let Some((scheme, _)) = specifier.split_once(':') else {
+    return false;
+  };
+  scheme.len() > 1
+    && scheme != "node"
+    && scheme.starts_with(|char: char| char.is_ascii_alphabetic())
+    && scheme
+      .chars()
+      .all(|char| char.is_ascii_alphanumeric() || matches!(char, '+' | '-' | '.'))
+}
+
+/// Whether a relative or absolute `specifier` only reached `path` by having an extension
+/// appended or a directory index looked up. Bare specifiers are left to their package.
+fn is_missing_extension(specifier: &str, path: &Path) -> bool {
+  if !(specifier.starts_with('.') || Path::new(specifier).is_absolute()) {
+    return false;
+  }
+  let specifier = specifier.split(['?', '#']).next().unwrap_or_default();
+  let requested = specifier.rsplit(['/', '\\']).next().unwrap_or_default();
+  if matches!(requested, "" | "." | "..") {
+    return true;
+  }
+  let file_name = path
+    .file_name()
+    .and_then(|name| name.to_str())
+    .unwrap_or_default();
+  let directory_name = path
+    .parent()
+    .and_then(Path::file_name)
+    .and_then(|name| name.to_str());
+  file_name != requested
+    && (file_name.starts_with(&format!("{requested}.")) || directory_name == Some(requested))
+}
+
+/// The on-disk casing of `path` if it differs from `path`. Components shared with
+/// `directory` belong to the importer and are not checked.
+fn case_mismatch(directory: &Path, path: &Path) -> Option<PathBuf> {
+  let mut actual = PathBuf::new();
+  let mut mismatch = false;
+  for component in path.components() {
+    let Component::Normal(name) = component else {
+      actual.push(component);
+      continue;
+    };
+    if !mismatch && directory.starts_with(actual.join(name)) {
+      actual.push(name);
+      continue;
+    }
+    let entries: Vec<_> = std::fs::read_dir(&actual)
+      .map(|entries| {
+        entries
+          .filter_map(|entry| entry.ok().map(|entry| entry.file_name()))
+          .collect()
+      })
+      .unwrap_or_default();
+    let lowercase_name = name.to_string_lossy().to_lowercase();
+    match entries
+      .iter()
+      .find(|entry| entry.to_string_lossy().to_lowercase() == lowercase_name)
+    {
+      Some(on_disk) if !entries.iter().any(|entry| entry == name) => {
+        mismatch = true;
+        actual.push(on_disk);
+      }
+      _ => actual.push(name),
+    }
+  }
+  mismatch.then_some(actual)
+}
+
+/// Where a referenced tsconfig project is built from and built into.
+struct ProjectOutput {
+  out_dir: PathBuf,
+  /// `rootDir`, or `src` and then the tsconfig's own directory when it's left out
+  source_dirs: Vec<PathBuf>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectOutputSerde {
+  #[serde(default)]
+  compiler_options: ProjectOutputOptionsSerde,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectOutputOptionsSerde {
+  out_dir: Option<PathBuf>,
+  root_dir: Option<PathBuf>,
+}
+
+impl ProjectOutput {
+  /// Reads `outDir` and `rootDir` of `tsconfig` itself, not of the tsconfigs it extends,
+  /// `None` for projects without an `outDir`.
+  fn read(tsconfig: &Path) -> Option<Self> {
+    let mut json = std::fs::read_to_string(tsconfig).ok()?;
+    json_strip_comments::strip(&mut json).ok()?;
+    let options = serde_json::from_str::<ProjectOutputSerde>(json.trim_start_matches('\u{feff}'))
+      .ok()?
+      .compiler_options;
+    let directory = tsconfig.parent()?;
+    Some(Self {
+      out_dir: directory.normalize_with(options.out_dir?),
+      source_dirs: match options.root_dir {
+        Some(root_dir) => vec![directory.normalize_with(root_dir)],
+        None => vec![directory.join("src"), directory.to_path_buf()],
+      },
+    })
+  }
+
+  /// The first existing source of the built file `path`, trying the extensions TypeScript
+  /// compiles to each output extension.
+  fn source_for(&self, path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(&self.out_dir).ok()?.to_str()?;
+    let (stem, extensions): (&str, &[&str]) = [
+      (".d.ts", &[".ts", ".tsx"][..]),
+      (".d.mts", &[".mts"]),
+      (".d.cts", &[".cts"]),
+      (".js", &[".ts", ".tsx", ".js", ".jsx"]),
+      (".mjs", &[".mts", ".mjs"]),
+      (".cjs", &[".cts", ".cjs"]),
+    ]
+    .iter()
+    .find_map(|(output, extensions)| Some((relative.strip_suffix(output)?, *extensions)))?;
+    self.source_dirs.iter().find_map(|source_dir| {
+      extensions
+        .iter()
+        .map(|extension| source_dir.join(format!("{}{}", stem, extension)))
+        .find(|source| source.is_file())
+    })
+  }
+}
+
+/// The nearest `tsconfig.json` or `jsconfig.json` in or above `directory`, tsconfigs of
+/// dependencies don't apply to the project importing them.
+fn find_tsconfig(directory: &Path) -> Option<PathBuf> {
+  if directory
+    .components()
+    .any(|component| component.as_os_str() == "node_modules")
+  {
+    return None;
+  }
+  directory.ancestors().find_map(tsconfig_in)
+}
+
+/// The `tsconfig.json` of `directory`, or its `jsconfig.json` in plain JS projects, the
+/// same precedence TypeScript uses.
+fn tsconfig_in(directory: &Path) -> Option<PathBuf> {
+  ["tsconfig.json", "jsconfig.json"]
+    .iter()
+    .map(|file_name| directory.join(file_name))
+    .find(|tsconfig| tsconfig.is_file())
+}
+
+/// The directory `importer` is resolved from, `/` for `/` itself and the current
+/// directory for a bare file name.
+fn importer_directory(importer: &str) -> &Path {
+  match Path::new(importer).parent() {
+    None => Path::new(importer),
+    Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+    Some(parent) => parent,
+  }
+}
+
+/// Rewrites the separators of a native path, only Windows paths change since `/` is the
+/// native separator everywhere else.
+fn normalize_separators(path: String, separator: PathSeparator, main_separator: char) -> String {
+  match (separator, main_separator) {
+    (PathSeparator::Slash, '\\') => path.replace('\\', "/"),
+    (PathSeparator::Native, '\\') => path.replace('/', "\\"),
+    _ => path,
+  }
+}
+
+pub fn resolve_importee(
+  resolver: &ModuleResolver,
+  importer: &str,
+  importee: &str,
+) -> std::result::Result<String, ResolutionError> {
+  let resolution = resolver.resolve(importer_directory(importer), importee);
+  resolver.resolved_path(resolution, importer, importee)
+}
+
+pub fn resolve_from_directory(
+  resolver: &ModuleResolver,
+  context_dir: &str,
+  importee: &str,
+) -> std::result::Result<String, ResolutionError> {
+  let resolution = resolver.resolve(context_dir, importee);
+  resolver.resolved_path(resolution, context_dir, importee)
+}
+
+pub fn resolve_importee_details(
+  resolver: &ModuleResolver,
+  importer: &str,
+  importee: &str,
+) -> std::result::Result<ResolutionDetails, ResolutionError> {
+  let resolution = resolver.resolve(importer_directory(importer), importee);
+  resolver.resolution_details(resolution, importer, importee)
+}
+
+pub fn resolve_importees(
+  resolver: &ModuleResolver,
+  importer: &str,
+  importees: &[String],
+) -> Vec<ResolveManyResult> {
+  importees
+    .iter()
+    .map(|importee| resolve_importee(resolver, importer, importee).into())
+    .collect()
+}
+
+pub fn create_resolver(mut options: ResolverOptions) -> Result<ModuleResolver> {
+  let restrictions = options
+    .restrictions
+    .take()
+    .unwrap_or_default()
+    .into_iter()
+    .map(PathBuf::from)
+    .collect();
+  let path_separator = options
+    .path_separator
+    .take()
+    .unwrap_or(PathSeparator::Native);
+  let enforce_extension = options.enforce_extension.take().unwrap_or_default();
+  let tsconfig_resolvers = options
+    .discover_tsconfig
+    .take()
+    .unwrap_or_default()
+    .then(Mutex::default);
+  let case_sensitive = options.case_sensitive.take().unwrap_or_default();
+  let project_outputs = options
+    .prefer_reference_source
+    .take()
+    .unwrap_or_default()
+    .then(Mutex::default);
+  let resolver = Arc::new(Resolver::new(options.into_resolve_options()?));
+  let builtin_fallback = (!resolver.options().fallback.is_empty()).then(|| {
+    resolver.clone_with_options(ResolveOptions {
+      builtin_modules: false,
+      ..resolver.options().clone()
+    })
+  });
+  Ok(ModuleResolver {
+    resolver,
+    restrictions,
+    path_separator,
+    builtin_fallback,
+    enforce_extension,
+    tsconfig_resolvers,
+    case_sensitive,
+    project_outputs,
+  })
+}
+
+fn create_tsconfig_option(
+  config_file: String,
+  tsconfig_references: Option<Vec<String>>,
+) -> TsconfigOptions {
+  let config_file = PathBuf::from(config_file);
+  // oxc_resolver only looks up `tsconfig.json` in a directory
+  let config_file = if config_file.is_dir() {
+    tsconfig_in(&config_file).unwrap_or(config_file)
+  } else {
+    config_file
+  };
+  TsconfigOptions {
+    config_file,
+    references: match tsconfig_references {
+      None => TsconfigReferences::Auto,
+      Some(refs) => TsconfigReferences::Paths(refs.into_iter().map(PathBuf::from).collect()),
+    },
+  }
+}
+
+fn create_alias_option(aliases: Vec<(String, Vec<String>)>) -> Vec<(String, Vec<AliasValue>)> {
+  aliases
+    .into_iter()
+    .map(|(key, val)| {
+      let mapped_alias = if val.is_empty() {
+        vec![AliasValue::Ignore]
+      } else {
+        val.into_iter().map(AliasValue::Path).collect()
+      };
+
+      (key, mapped_alias)
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_resolver_handle_reuses_options() {
+    let handle = ResolverHandle::new(ResolverOptions {
+      extensions: Some(vec![".rs".to_string()]),
+      alias: Some(vec![(
+        "~".to_string(),
+        vec![env!("CARGO_MANIFEST_DIR").to_string()],
+      )]),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let importer = format!("{}/src/lib.rs", env!("CARGO_MANIFEST_DIR"));
+
+    let resolved = resolve_importee(&handle.resolver, &importer, "./lexer").unwrap();
+    assert!(resolved.ends_with("src/lexer.rs"));
+
+    let results = handle.resolve_many(
+      importer,
+      vec!["~/build".to_string(), "./missing".to_string()],
+    );
+    assert!(results[0].path.as_ref().unwrap().ends_with("build.rs"));
+    assert_eq!(
+      results[1].error.as_ref().unwrap().code,
+      ResolveErrorCode::NotFound
+    );
+  }
+
+  #[test]
+  fn test_resolve_task_computes_off_thread() {
+    let mut task = ResolveTask::new(
+      ResolverOptions {
+        extensions: Some(vec![".rs".to_string()]),
+        ..ResolverOptions::default()
+      },
+      format!("{}/src/lib.rs", env!("CARGO_MANIFEST_DIR")),
+      "./resolver".to_string(),
+    );
+    let resolved = std::thread::spawn(move || task.compute()).join().unwrap();
+    assert!(resolved.unwrap().unwrap().ends_with("src/resolver.rs"));
+  }
+
+  #[test]
+  fn test_resolution_details() {
+    let handle = ResolverHandle::new(ResolverOptions {
+      extensions: Some(vec![".json".to_string()]),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let details = resolve_importee_details(
+      &handle.resolver,
+      &format!("{}/index.js", env!("CARGO_MANIFEST_DIR")),
+      "./package?raw#top",
+    )
+    .unwrap();
+
+    assert!(details.path.ends_with("package.json"));
+    assert_eq!(
+      details.package_name.as_deref(),
+      Some("@devinmdavies/eslint-barrel-file-utils")
+    );
+    assert!(!details.is_node_module);
+    assert_eq!(details.query.as_deref(), Some("?raw"));
+    assert_eq!(details.fragment.as_deref(), Some("#top"));
+  }
+
+  #[test]
+  fn test_pnp_manifest() {
+    let fixture = format!("{}/fixtures/pnp", env!("CARGO_MANIFEST_DIR"));
+    let handle = ResolverHandle::new(ResolverOptions {
+      pnp_manifest: Some(format!("{}/.pnp.cjs", fixture)),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+
+    let resolved = resolve_importee(
+      &handle.resolver,
+      &format!("{}/src/index.js", fixture),
+      "dep",
+    )
+    .unwrap();
+    assert!(resolved.ends_with(".yarn/unplugged/dep-npm-1.0.0/node_modules/dep/index.js"));
+  }
+
+  #[test]
+  fn test_package_imports_field() {
+    let fixture = format!("{}/fixtures/imports", env!("CARGO_MANIFEST_DIR"));
+    let handle = ResolverHandle::new(ResolverOptions {
+      condition_names: Some(vec!["import".to_string()]),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let importer = format!("{}/src/index.js", fixture);
+
+    let resolved = resolve_importee(&handle.resolver, &importer, "#utils/date").unwrap();
+    assert!(resolved.ends_with("src/utils/date.js"));
+    let resolved = resolve_importee(&handle.resolver, &importer, "#config").unwrap();
+    assert!(resolved.ends_with("src/config.js"));
+  }
+
+  #[test]
+  fn test_symlinks_option() {
+    let fixture = format!("{}/fixtures/symlinks", env!("CARGO_MANIFEST_DIR"));
+    let importer = format!("{}/app/index.js", fixture);
+    let resolve = |symlinks| {
+      let resolver = create_resolver(ResolverOptions {
+        symlinks: Some(symlinks),
+        ..ResolverOptions::default()
+      })
+      .unwrap();
+      resolve_importee(&resolver, &importer, "./shared/index.js").unwrap()
+    };
+
+    assert!(resolve(true).ends_with("packages/shared/index.js"));
+    assert!(resolve(false).ends_with("app/shared/index.js"));
+  }
+
+  #[test]
+  fn test_extension_alias() {
+    let fixture = format!("{}/fixtures/extension-alias", env!("CARGO_MANIFEST_DIR"));
+    let handle = ResolverHandle::new(ResolverOptions {
+      extension_alias: Some(vec![(
+        ".js".to_string(),
+        vec![".ts".to_string(), ".js".to_string()],
+      )]),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+
+    let resolved = resolve_importee(
+      &handle.resolver,
+      &format!("{}/src/index.ts", fixture),
+      "./math.js",
+    )
+    .unwrap();
+    assert!(resolved.ends_with("src/math.ts"));
+  }
+
+  #[test]
+  fn test_alias_fields() {
+    let fixture = format!("{}/fixtures/browser-field", env!("CARGO_MANIFEST_DIR"));
+    let importer = format!("{}/src/index.js", fixture);
+    let resolve = |alias_fields| {
+      let resolver = create_resolver(ResolverOptions {
+        alias_fields,
+        ..ResolverOptions::default()
+      })
+      .unwrap();
+      resolve_importee(&resolver, &importer, "isomorphic").unwrap()
+    };
+
+    assert!(resolve(None).ends_with("lib/node.js"));
+    assert!(resolve(Some(vec![vec!["browser".to_string()]])).ends_with("lib/browser.js"));
+  }
+
+  #[test]
+  fn test_resolution_error_codes() {
+    let importer = format!("{}/src/lib.rs", env!("CARGO_MANIFEST_DIR"));
+    let resolver = create_resolver(ResolverOptions::default()).unwrap();
+    let error = resolve_importee(&resolver, &importer, "./missing").unwrap_err();
+    assert_eq!(error.code, ResolveErrorCode::NotFound);
+    assert_eq!(error.specifier, "./missing");
+    assert_eq!(error.importer, importer);
+
+    let resolver = create_resolver(ResolverOptions {
+      tsconfig: Some(TsconfigResolveOptions {
+        config_file: format!("{}/tsconfig.missing.json", env!("CARGO_MANIFEST_DIR")),
+        references: None,
+      }),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let error = resolve_importee(&resolver, &importer, "./lexer").unwrap_err();
+    assert_eq!(error.code, ResolveErrorCode::TsconfigError);
+  }
+
+  #[test]
+  fn test_cache_stats_and_clear() {
+    let handle = ResolverHandle::new(ResolverOptions {
+      extensions: Some(vec![".rs".to_string()]),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let importer = format!("{}/src/lib.rs", env!("CARGO_MANIFEST_DIR"));
+    let sibling = format!("{}/src/resolver.rs", env!("CARGO_MANIFEST_DIR"));
+
+    let importees = vec!["./lexer".to_string(), "./missing".to_string()];
+    handle.resolve_many(importer.clone(), importees.clone());
+    let results = handle.resolve_many(sibling, importees);
+    assert!(results[0].path.as_ref().unwrap().ends_with("src/lexer.rs"));
+    assert!(results[1].error.is_some());
+
+    // Failures aren't cached
+    let stats = handle.cache_stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 3, 1));
+
+    handle.clear_cache();
+    let stats = handle.cache_stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (0, 0, 0));
+  }
+
+  #[test]
+  fn test_restrictions() {
+    let src = format!("{}/src", env!("CARGO_MANIFEST_DIR"));
+    let resolver = create_resolver(ResolverOptions {
+      extensions: Some(vec![".rs".to_string(), ".js".to_string()]),
+      restrictions: Some(vec![src.clone()]),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let importer = format!("{}/lib.rs", src);
+
+    assert!(resolve_importee(&resolver, &importer, "./lexer").is_ok());
+    let error = resolve_importee(&resolver, &importer, "../index").unwrap_err();
+    assert_eq!(error.code, ResolveErrorCode::Restricted);
+  }
+
+  #[test]
+  fn test_tsconfig_paths_match() {
+    let fixture = format!("{}/fixtures/tsconfig-paths", env!("CARGO_MANIFEST_DIR"));
+    let resolver = create_resolver(ResolverOptions {
+      extensions: Some(vec![".ts".to_string()]),
+      tsconfig: Some(TsconfigResolveOptions {
+        config_file: format!("{}/tsconfig.json", fixture),
+        references: None,
+      }),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let importer = format!("{}/src/index.ts", fixture);
+
+    let details = resolve_importee_details(&resolver, &importer, "@components/button").unwrap();
+    assert!(details.path.ends_with("src/components/button.ts"));
+    let tsconfig_paths = details.tsconfig_paths.unwrap();
+    assert_eq!(tsconfig_paths.pattern, "@components/*");
+    assert_eq!(tsconfig_paths.target, "src/components/*");
+
+    let details = resolve_importee_details(&resolver, &importer, "./helper").unwrap();
+    assert!(details.tsconfig_paths.is_none());
+  }
+
+  #[test]
+  fn test_resolve_from_directory() {
+    let resolver = create_resolver(ResolverOptions {
+      extensions: Some(vec![".rs".to_string()]),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+
+    let resolved =
+      resolve_from_directory(&resolver, env!("CARGO_MANIFEST_DIR"), "./src/lib").unwrap();
+    assert!(resolved.ends_with("src/lib.rs"));
+
+    // Root-level and bare importers have no usable parent
+    let error = resolve_importee(&resolver, "/", "./missing").unwrap_err();
+    assert_eq!(error.code, ResolveErrorCode::NotFound);
+    assert!(resolve_importee(&resolver, "index.js", "./missing").is_err());
+  }
+
+  #[test]
+  fn test_builtin_modules() {
+    let resolver = create_resolver(ResolverOptions::default()).unwrap();
+    let importer = format!("{}/index.js", env!("CARGO_MANIFEST_DIR"));
+
+    assert_eq!(
+      resolve_importee(&resolver, &importer, "path").unwrap(),
+      "node:path"
+    );
+    assert_eq!(
+      resolve_importee(&resolver, &importer, "node:fs").unwrap(),
+      "node:fs"
+    );
+    let details = resolve_importee_details(&resolver, &importer, "fs/promises").unwrap();
+    assert!(details.is_builtin);
+    assert_eq!(details.path, "node:fs/promises");
+  }
+
+  #[test]
+  fn test_url_specifiers() {
+    let resolver = create_resolver(ResolverOptions::default()).unwrap();
+    let importer = format!("{}/index.js", env!("CARGO_MANIFEST_DIR"));
+
+    for url in [
+      "https://esm.sh/react",
+      "data:text/javascript,export default 1",
+      "file:///tmp/module.js",
+    ] {
+      assert_eq!(resolve_importee(&resolver, &importer, url).unwrap(), url);
+    }
+    assert!(
+      resolve_importee_details(&resolver, &importer, "https://esm.sh/react")
+        .unwrap()
+        .is_url
+    );
+
+    assert!(!is_url_specifier("C:\\projects\\index.js"));
+    assert!(!is_url_specifier("@scope/pkg"));
+    assert!(!is_url_specifier("node:fs"));
+  }
+
+  #[test]
+  fn test_normalize_separators() {
+    let windows_path = || r"C:\project\src/index.js".to_string();
+    assert_eq!(
+      normalize_separators(windows_path(), PathSeparator::Slash, '\\'),
+      "C:/project/src/index.js"
+    );
+    assert_eq!(
+      normalize_separators(windows_path(), PathSeparator::Native, '\\'),
+      r"C:\project\src\index.js"
+    );
+    assert_eq!(
+      normalize_separators(
+        "/project/src/index.js".to_string(),
+        PathSeparator::Slash,
+        '/'
+      ),
+      "/project/src/index.js"
+    );
+  }
+
+  #[test]
+  fn test_fallback_polyfills_builtins() {
+    let fixture = format!("{}/fixtures/browser-field", env!("CARGO_MANIFEST_DIR"));
+    let resolver = create_resolver(ResolverOptions {
+      fallback: Some(vec![("crypto".to_string(), vec!["isomorphic".to_string()])]),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let importer = format!("{}/src/index.js", fixture);
+
+    let resolved = resolve_importee(&resolver, &importer, "crypto").unwrap();
+    assert!(resolved.ends_with("isomorphic/lib/node.js"));
+    assert_eq!(
+      resolve_importee(&resolver, &importer, "path").unwrap(),
+      "node:path"
+    );
+  }
+
+  #[test]
+  fn test_package_for_path() {
+    let fixture = format!("{}/fixtures/browser-field", env!("CARGO_MANIFEST_DIR"));
+    let resolver = create_resolver(ResolverOptions::default()).unwrap();
+
+    let package = resolver
+      .package_for_path(&format!("{}/node_modules/isomorphic/lib/node.js", fixture))
+      .unwrap();
+    assert_eq!(package.name.as_deref(), Some("isomorphic"));
+    assert_eq!(package.version.as_deref(), Some("1.2.3"));
+    assert!(
+      package
+        .package_json_path
+        .ends_with("isomorphic/package.json")
+    );
+
+    let details = resolve_importee_details(
+      &resolver,
+      &format!("{}/src/index.js", fixture),
+      "isomorphic",
+    )
+    .unwrap();
+    assert_eq!(details.package_version.as_deref(), Some("1.2.3"));
+    assert!(
+      resolver
+        .package_for_path(&format!("{}/missing.js", fixture))
+        .is_none()
+    );
+  }
+
+  #[test]
+  fn test_enforce_extension() {
+    let resolver = create_resolver(ResolverOptions {
+      extensions: Some(vec![".rs".to_string()]),
+      enforce_extension: Some(true),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let importer = format!("{}/src/lib.rs", env!("CARGO_MANIFEST_DIR"));
+
+    assert!(resolve_importee(&resolver, &importer, "./lexer.rs").is_ok());
+    let error = resolve_importee(&resolver, &importer, "./lexer").unwrap_err();
+    assert_eq!(error.code, ResolveErrorCode::MissingExtension);
+    let error = resolve_importee(&resolver, &importer, "./missing").unwrap_err();
+    assert_eq!(error.code, ResolveErrorCode::NotFound);
+  }
+
+  #[test]
+  fn test_discover_tsconfig() {
+    let fixture = format!("{}/fixtures/tsconfig-discovery", env!("CARGO_MANIFEST_DIR"));
+    let resolver = create_resolver(ResolverOptions {
+      extensions: Some(vec![".ts".to_string()]),
+      discover_tsconfig: Some(true),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+
+    let importer = format!("{}/packages/app/src/index.ts", fixture);
+    let resolved = resolve_importee(&resolver, &importer, "@lib/util").unwrap();
+    assert!(resolved.ends_with("packages/app/src/lib/util.ts"));
+
+    let importer = format!("{}/packages/shared/index.ts", fixture);
+    let details = resolve_importee_details(&resolver, &importer, "@lib/util").unwrap();
+    assert!(details.path.ends_with("packages/shared/lib/util.ts"));
+    assert_eq!(details.tsconfig_paths.unwrap().target, "lib/*");
+  }
+
+  #[test]
+  fn test_case_mismatch() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    assert_eq!(case_mismatch(root, &root.join("src/lexer.rs")), None);
+    assert_eq!(
+      case_mismatch(root, &root.join("SRC/Lexer.rs")),
+      Some(root.join("src/lexer.rs"))
+    );
+    // The importer's own directory is taken as given
+    assert_eq!(
+      case_mismatch(&root.join("SRC"), &root.join("SRC/lexer.rs")),
+      None
+    );
+  }
+
+  #[test]
+  fn test_jsconfig_paths() {
+    let fixture = format!("{}/fixtures/jsconfig-paths", env!("CARGO_MANIFEST_DIR"));
+    let importer = format!("{}/src/index.js", fixture);
+
+    let resolver = create_resolver(ResolverOptions {
+      tsconfig: Some(TsconfigResolveOptions {
+        config_file: fixture.clone(),
+        references: None,
+      }),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let resolved = resolve_importee(&resolver, &importer, "@utils/format").unwrap();
+    assert!(resolved.ends_with("src/utils/format.js"));
+
+    let resolver = create_resolver(ResolverOptions {
+      discover_tsconfig: Some(true),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let resolved = resolve_importee(&resolver, &importer, "@utils/format").unwrap();
+    assert!(resolved.ends_with("src/utils/format.js"));
+  }
+
+  #[test]
+  fn test_malformed_jsconfig_errors() {
+    let fixture = format!("{}/fixtures/malformed-jsconfig", env!("CARGO_MANIFEST_DIR"));
+    let importer = format!("{}/src/index.js", fixture);
+    let resolve = |options| {
+      let resolver = create_resolver(options).unwrap();
+      resolve_importee(&resolver, &importer, "./format.js").unwrap_err()
+    };
+
+    let error = resolve(ResolverOptions {
+      tsconfig: Some(TsconfigResolveOptions {
+        config_file: format!("{}/jsconfig.json", fixture),
+        references: None,
+      }),
+      ..ResolverOptions::default()
+    });
+    assert_eq!(error.code, ResolveErrorCode::TsconfigError);
+    let error = resolve(ResolverOptions {
+      discover_tsconfig: Some(true),
+      ..ResolverOptions::default()
+    });
+    assert_eq!(error.code, ResolveErrorCode::TsconfigError);
+  }
+
+  #[test]
+  fn test_prefer_reference_source() {
+    let fixture = format!(
+      "{}/fixtures/tsconfig-references",
+      env!("CARGO_MANIFEST_DIR")
+    );
+    let importer = format!("{}/packages/app/src/index.ts", fixture);
+    let tsconfig = Some(TsconfigResolveOptions {
+      config_file: format!("{}/packages/app", fixture),
+      references: None,
+    });
+
+    let resolver = create_resolver(ResolverOptions {
+      tsconfig: tsconfig.clone(),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let resolved = resolve_importee(&resolver, &importer, "@acme/shared").unwrap();
+    assert!(resolved.ends_with("packages/shared/dist/index.js"));
+
+    let resolver = create_resolver(ResolverOptions {
+      tsconfig,
+      prefer_reference_source: Some(true),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let details = resolve_importee_details(&resolver, &importer, "@acme/shared").unwrap();
+    assert!(details.path.ends_with("packages/shared/src/index.ts"));
+    assert_eq!(details.package_name.as_deref(), Some("@acme/shared"));
+
+    // Explicit references and discovered tsconfigs redirect the same way
+    let resolver = create_resolver(ResolverOptions {
+      tsconfig: Some(TsconfigResolveOptions {
+        config_file: format!("{}/packages/app", fixture),
+        references: Some(vec!["../shared".to_string()]),
+      }),
+      prefer_reference_source: Some(true),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let resolved = resolve_importee(&resolver, &importer, "@acme/shared").unwrap();
+    assert!(resolved.ends_with("packages/shared/src/index.ts"));
+
+    let resolver = create_resolver(ResolverOptions {
+      discover_tsconfig: Some(true),
+      prefer_reference_source: Some(true),
+      ..ResolverOptions::default()
+    })
+    .unwrap();
+    let importer = format!("{}/packages/shared/dist/index.js", fixture);
+    let resolved = resolve_importee(&resolver, &importer, "./format.js").unwrap();
+    assert!(resolved.ends_with("packages/shared/dist/format.js"));
+    let importer = format!("{}/packages/app/src/index.ts", fixture);
+    let resolved = resolve_importee(&resolver, &importer, "@acme/shared").unwrap();
+    assert!(resolved.ends_with("packages/shared/src/index.ts"));
+  }
+
+  #[test]
+  fn test_resolution_cache_sees_created_files() {
+    let directory = std::env::temp_dir().join(format!("resolver-cache-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let resolver = create_resolver(ResolverOptions::default()).unwrap();
+    let cache = ResolutionCache::with_capacity(1);
+
+    assert!(cache.resolve(&resolver, &directory, "./created").is_err());
+    std::fs::write(directory.join("created.js"), "").unwrap();
+    std::fs::write(directory.join("other.js"), "").unwrap();
+    // The failure wasn't memoized, only the resolver's filesystem cache needs clearing
+    resolver.clear_cache();
+    let resolution = cache.resolve(&resolver, &directory, "./created");
+    assert!(resolution.is_ok());
+
+    // The oldest entry makes room
+    assert!(cache.resolve(&resolver, &directory, "./other").is_ok());
+    assert!(cache.resolve(&resolver, &directory, "./created").is_ok());
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (0, 4, 1));
+    std::fs::remove_dir_all(&directory).unwrap();
+  }
+}
@@ -0,0 +1,104 @@
+//! `<script>` extraction for Vue and Svelte components, so the barrel classification and
+//! module graph parse `.vue` and `.svelte` files without choking on their markup.
+
+use oxc_span::SourceType;
+
+/// Whether `path` is a Vue or Svelte component.
+pub fn is_component_file(path: &str) -> bool {
+  path.ends_with(".vue") || path.ends_with(".svelte")
+}
+
+/// `source` with everything but the contents of its `<script>` blocks, such as Vue's
+/// `<script setup>` or Svelte's `<script context="module">`, blanked out, so offsets and
+/// lines still point into the component, and the source type their `lang` attribute selects,
+/// plain ES modules by default.
+pub fn script_source(source: &str) -> (String, SourceType) {
+  let mut script = vec![b' '; source.len()];
+  let mut lang = None;
+  let mut rest = 0;
+  while let Some((open, content)) = next_script_tag(source, rest) {
+    let Some(close) = source[content..]
+      .find("</script>")
+      .map(|close| content + close)
+    else {
+      break;
+    };
+    lang = lang.or_else(|| attribute(&source[open..content], "lang"));
+    script[content..close].copy_from_slice(&source.as_bytes()[content..close]);
+    rest = close + "</script>".len();
+  }
+  for (index, byte) in source.bytes().enumerate() {
+    if byte == b'\n' {
+      script[index] = b'\n';
+    }
+  }
+
+  let source_type = match lang {
+    Some("ts") => SourceType::ts(),
+    Some("tsx") => SourceType::tsx(),
+    Some("jsx") => SourceType::jsx(),
+    _ => SourceType::mjs(),
+  };
+  // The markup is replaced byte for byte with ASCII and the scripts are copied whole
+  (String::from_utf8(script).unwrap(), source_type)
+}
+
+/// The start of the next `<script ...>` tag from `from` and the end of the tag, where the
+/// block's contents start.
+fn next_script_tag(source: &str, from: usize) -> Option<(usize, usize)> {
+  let mut from = from;
+  loop {
+    let open = from + source[from..].find("<script")?;
+    let after = open + "<script".len();
+    if source[after..].starts_with(|char: char| char == '>' || char.is_ascii_whitespace()) {
+      let content = after + source[after..].find('>')? + 1;
+      return Some((open, content));
+    }
+    from = after;
+  }
+}
+
+/// The value of `name` in the attributes of `tag`, e.g. `ts` for `<script setup lang="ts">`.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+  let value = tag
+    .split_ascii_whitespace()
+    .find_map(|attribute| attribute.strip_prefix(name)?.strip_prefix('='))?;
+  let value = value.trim_end_matches('>');
+  Some(value.trim_matches(|char| char == '"' || char == '\''))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_script_source() {
+    let source = "<template>\n  <Button />\n</template>\n\n<script setup lang=\"ts\">\nimport Button from './Button.vue';\n</script>\n\n<style>\n.a { color: red }\n</style>\n";
+    let (script, source_type) = script_source(source);
+
+    assert_eq!(script.len(), source.len());
+    assert_eq!(script.lines().count(), source.lines().count());
+    assert_eq!(
+      script.trim(),
+      "import Button from './Button.vue';".to_string()
+    );
+    assert_eq!(
+      script.find("import"),
+      source.find("import"),
+      "offsets are kept"
+    );
+    assert!(source_type.is_typescript());
+    assert!(!script_source("<template />\n").1.is_typescript());
+  }
+
+  #[test]
+  fn test_svelte_script_source() {
+    let source = "<script context=\"module\" lang=\"ts\">\n  export const prerender = true;\n</script>\n\n<script lang=\"ts\">\n  export let name: string;\n</script>\n\n<h1>Hello {name}!</h1>\n";
+    let (script, source_type) = script_source(source);
+
+    assert!(source_type.is_typescript());
+    assert!(script.contains("export const prerender = true;"));
+    assert!(script.contains("export let name: string;"));
+    assert!(!script.contains("<h1>"));
+  }
+}
@@ -0,0 +1,386 @@
+//! Watchers that keep the graphs of a `ModuleGraphAnalyzer` up to date while their modules
+//! change on disk, for editor integrations.
+//!
+//! Each watcher subscribes to the filesystem events of the modules of its graph and of the
+//! directories they are in, the latter catching modules created since the last walk as well as
+//! editors that save by replacing files. Events are handled on a thread of the watcher's own,
+//! which walks the graph again once they stop coming for `debounce_ms`.
+
+use napi::bindgen_prelude::FromNapiValue;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, Error, Result, Status::GenericFailure, sys};
+use napi_derive::napi;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::module_graph::{AnalyzerState, ModuleGraph, ModuleGraphSize};
+
+/// A watched graph after a change to its modules changed its `count` or `size`.
+#[napi(object)]
+pub struct ModuleGraphChange {
+  /// The entry points of the graph, as passed to `watch`
+  pub entry_points: Vec<String>,
+  /// The modules that changed on disk, relative to `base_path`, in the order of the graph
+  /// before the change. Empty when only files next to them were created or deleted
+  pub changed: Vec<String>,
+  /// `graph.count` before the change
+  pub previous_count: u32,
+  /// `graph.size` before the change
+  pub previous_size: f64,
+  pub graph: ModuleGraphSize,
+}
+
+/// `on_change` behind a trait object, like `on_progress`. Errors walking the graph again,
+/// such as for a deleted entry point, are passed as the callback's error argument.
+#[derive(Clone)]
+pub struct ChangeCallback(Arc<dyn Fn(Result<ModuleGraphChange>) + Send + Sync>);
+
+impl FromNapiValue for ChangeCallback {
+  unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+    let mut on_change = unsafe {
+      ThreadsafeFunction::<ModuleGraphChange, ErrorStrategy::CalleeHandled>::from_napi_value(
+        env, napi_val,
+      )?
+    };
+    // Like a `fs.watch` that isn't `persistent`, a watcher doesn't keep the process alive
+    on_change.unref(&unsafe { Env::from_raw(env) })?;
+    Ok(Self(Arc::new(move |change| {
+      on_change.call(change, ThreadsafeFunctionCallMode::NonBlocking);
+    })))
+  }
+}
+
+enum Message {
+  Event(notify::Result<Event>),
+  Stop,
+}
+
+/// Watches the modules of a graph until it is closed or garbage collected.
+#[napi]
+pub struct ModuleGraphWatcher {
+  messages: Sender<Message>,
+  thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+#[napi]
+impl ModuleGraphWatcher {
+  /// Stops watching, after the check in progress if there is one.
+  #[napi]
+  pub fn close(&self) {
+    let _ = self.messages.send(Message::Stop);
+    if let Some(thread) = self.thread.lock().unwrap().take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+impl Drop for ModuleGraphWatcher {
+  fn drop(&mut self) {
+    let _ = self.messages.send(Message::Stop);
+  }
+}
+
+impl ModuleGraphWatcher {
+  pub fn start(
+    state: Arc<AnalyzerState>,
+    entry_points: Vec<String>,
+    base_path: String,
+    on_change: ChangeCallback,
+    debounce: Duration,
+  ) -> Result<Self> {
+    let (messages, received) = mpsc::channel();
+    let events = messages.clone();
+    let watcher = notify::recommended_watcher(move |event| {
+      let _ = events.send(Message::Event(event));
+    })
+    .map_err(watch_error)?;
+    // Subscribed before returning, so no change made once `watch` returns is missed
+    let mut watched = WatchedGraph {
+      graph: state.recount(&entry_points, &base_path)?,
+      base_path,
+      watcher,
+      watched: HashSet::new(),
+    };
+    watched.watch_nodes()?;
+    let thread = thread::spawn(move || {
+      while let Some(paths) = next_changes(&received, debounce) {
+        let change = match paths {
+          Ok(paths) => watched.check(&state, &entry_points, &paths),
+          Err(error) => Some(Err(error)),
+        };
+        if let Some(change) = change {
+          (on_change.0)(change);
+        }
+      }
+    });
+    Ok(Self {
+      messages,
+      thread: Mutex::new(Some(thread)),
+    })
+  }
+}
+
+/// The paths of the next events, waiting until none came for `debounce` as a save often
+/// causes several. `None` once the watcher is stopped.
+fn next_changes(
+  received: &Receiver<Message>,
+  debounce: Duration,
+) -> Option<Result<HashSet<PathBuf>>> {
+  let mut paths = HashSet::new();
+  loop {
+    match received.recv() {
+      Ok(Message::Event(event)) => {
+        if let Err(error) = add_event(&mut paths, event) {
+          return Some(Err(error));
+        }
+      }
+      Ok(Message::Stop) | Err(_) => return None,
+    }
+    if !paths.is_empty() {
+      break;
+    }
+  }
+  loop {
+    match received.recv_timeout(debounce) {
+      Ok(Message::Event(event)) => {
+        if let Err(error) = add_event(&mut paths, event) {
+          return Some(Err(error));
+        }
+      }
+      Err(RecvTimeoutError::Timeout) => return Some(Ok(paths)),
+      Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => return None,
+    }
+  }
+}
+
+fn add_event(paths: &mut HashSet<PathBuf>, event: notify::Result<Event>) -> Result<()> {
+  match event {
+    // Reads, such as walking the graph, aren't changes
+    Ok(event) if matches!(event.kind, EventKind::Access(_)) => Ok(()),
+    Ok(event) => {
+      paths.extend(event.paths);
+      Ok(())
+    }
+    Err(error) => Err(watch_error(error)),
+  }
+}
+
+fn watch_error(error: notify::Error) -> Error {
+  Error::new(
+    GenericFailure,
+    format!("Failed to watch the module graph, message: \"{}\"", error),
+  )
+}
+
+/// A graph and the watches on its nodes and their directories.
+struct WatchedGraph {
+  graph: ModuleGraph,
+  base_path: String,
+  watcher: RecommendedWatcher,
+  /// The files and directories watched
+  watched: HashSet<PathBuf>,
+}
+
+impl WatchedGraph {
+  fn path(&self, node: &str) -> PathBuf {
+    Path::new(&self.base_path).join(node)
+  }
+
+  /// Watches the nodes of the graph that are files and their directories, and stops watching
+  /// those of nodes no longer in it.
+  fn watch_nodes(&mut self) -> Result<()> {
+    let mut paths = HashSet::new();
+    for node in &self.graph.nodes {
+      let path = self.path(node);
+      if !path.is_file() {
+        continue;
+      }
+      if let Some(directory) = path.parent() {
+        paths.insert(directory.to_path_buf());
+      }
+      paths.insert(path);
+    }
+    for path in self.watched.difference(&paths) {
+      // Fails for deleted files, which aren't watched anymore anyway
+      let _ = self.watcher.unwatch(path);
+    }
+    for path in paths.difference(&self.watched) {
+      self
+        .watcher
+        .watch(path, RecursiveMode::NonRecursive)
+        .map_err(watch_error)?;
+    }
+    self.watched = paths;
+    Ok(())
+  }
+
+  /// Walks the graph again after events on `paths`, the change being reported when the
+  /// graph's `count` or `size` differs afterwards.
+  fn check(
+    &mut self,
+    state: &AnalyzerState,
+    entry_points: &[String],
+    paths: &HashSet<PathBuf>,
+  ) -> Option<Result<ModuleGraphChange>> {
+    let changed: Vec<String> = self
+      .graph
+      .nodes
+      .iter()
+      .filter(|node| paths.contains(&self.path(node)))
+      .cloned()
+      .collect();
+    // Files created or deleted next to the nodes, such as one an import now resolves to
+    let directories: HashSet<&Path> = paths
+      .iter()
+      .filter(|path| !self.watched.contains(*path))
+      .filter_map(|path| path.parent())
+      .filter(|directory| self.watched.contains(*directory))
+      .collect();
+    if changed.is_empty() && directories.is_empty() {
+      return None;
+    }
+
+    // The importers of a deleted module resolve its imports again, to whatever takes its
+    // place, and so do the modules next to and the importers of the modules next to a file
+    // created or deleted
+    let in_directory = |node: &str| {
+      self
+        .path(node)
+        .parent()
+        .is_some_and(|directory| directories.contains(directory))
+    };
+    let deleted =
+      |node: &str| changed.iter().any(|changed| changed == node) && !self.path(node).exists();
+    let mut invalidated: HashSet<&str> = changed.iter().map(String::as_str).collect();
+    invalidated.extend(
+      self
+        .graph
+        .nodes
+        .iter()
+        .map(String::as_str)
+        .filter(|node| in_directory(node)),
+    );
+    for edge in self.graph.edges.iter().chain(&self.graph.dynamic_edges) {
+      if deleted(&edge.importee) || in_directory(&edge.importee) {
+        invalidated.insert(&edge.importer);
+      }
+    }
+    if !directories.is_empty() || changed.iter().any(|node| deleted(node)) {
+      state.clear_resolutions();
+    }
+    for module in invalidated {
+      state.invalidate(module);
+    }
+
+    let graph = match state.recount(entry_points, &self.base_path) {
+      Ok(graph) => graph,
+      // Reported once, the graph is walked again on its next change
+      Err(error) => return Some(Err(error)),
+    };
+    let previous = std::mem::replace(&mut self.graph, graph);
+    // A file an editor saved by replacing it is watched again
+    for node in &changed {
+      self.watched.remove(&self.path(node));
+    }
+    if let Err(error) = self.watch_nodes() {
+      return Some(Err(error));
+    }
+    if previous.nodes.len() == self.graph.nodes.len() && previous.size == self.graph.size {
+      return None;
+    }
+    Some(Ok(ModuleGraphChange {
+      entry_points: entry_points.to_vec(),
+      changed,
+      previous_count: previous.nodes.len() as u32,
+      previous_size: previous.size,
+      graph: ModuleGraphSize::from(self.graph.clone()),
+    }))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::module_graph::{ModuleGraphAnalyzer, ModuleGraphOptions, UnresolvedImports};
+  use crate::resolver::ResolverOptions;
+
+  fn next_change(changes: &Receiver<Result<ModuleGraphChange>>) -> Result<ModuleGraphChange> {
+    changes.recv_timeout(Duration::from_secs(5)).unwrap()
+  }
+
+  #[test]
+  fn test_watcher_reports_size_changes() {
+    let base_path = std::env::temp_dir().join(format!("module-graph-watch-{}", std::process::id()));
+    std::fs::create_dir_all(&base_path).unwrap();
+    std::fs::write(
+      base_path.join("index.js"),
+      "import './a.js';\nimport './c.js';\n",
+    )
+    .unwrap();
+    std::fs::write(base_path.join("a.js"), "export const a = 1;\n").unwrap();
+    std::fs::write(base_path.join("b.js"), "export const b = 1;\n").unwrap();
+
+    let analyzer = ModuleGraphAnalyzer::new(
+      vec![],
+      vec![],
+      ResolverOptions::default(),
+      Some(ModuleGraphOptions {
+        on_unresolved: Some(UnresolvedImports::Ignore),
+        ..ModuleGraphOptions::default()
+      }),
+    )
+    .unwrap();
+    let (sender, changes) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    let watcher = analyzer
+      .watch(
+        vec!["./index.js".to_string()],
+        base_path.to_str().unwrap().to_string(),
+        ChangeCallback(Arc::new(move |change| {
+          sender.lock().unwrap().send(change).unwrap();
+        })),
+        Some(10),
+      )
+      .unwrap();
+
+    std::fs::write(base_path.join("a.js"), "import './b.js';\n").unwrap();
+    let change = next_change(&changes).unwrap();
+    assert_eq!(change.entry_points, vec!["./index.js"]);
+    assert_eq!(change.changed, vec!["a.js"]);
+    assert_eq!((change.previous_count, change.graph.count), (2, 3));
+    assert_eq!(change.graph.modules, vec!["index.js", "a.js", "b.js"]);
+
+    // The graph the watcher walked is the one `recount` answers from
+    let stats = analyzer.cache_stats();
+    assert_eq!(
+      analyzer
+        .recount(
+          vec!["./index.js".to_string()],
+          base_path.to_str().unwrap().to_string()
+        )
+        .unwrap()
+        .count,
+      3
+    );
+    assert_eq!(analyzer.cache_stats().hits, stats.hits);
+
+    // A module created since, which an import failed to resolve to
+    std::fs::write(base_path.join("c.js"), "export const c = 1;\n").unwrap();
+    let change = next_change(&changes).unwrap();
+    assert!(change.changed.is_empty());
+    assert_eq!((change.previous_count, change.graph.count), (3, 4));
+
+    std::fs::remove_file(base_path.join("b.js")).unwrap();
+    let change = next_change(&changes).unwrap();
+    assert_eq!(change.changed, vec!["b.js"]);
+    assert_eq!((change.previous_count, change.graph.count), (4, 3));
+
+    watcher.close();
+    std::fs::remove_dir_all(&base_path).unwrap();
+  }
+}